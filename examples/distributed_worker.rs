@@ -0,0 +1,70 @@
+//! Worker of distributed Game of Life. Connects to
+//! coordinator at address given as the first argument
+//! and evolves its strip until coordinator stops it.
+//!
+//! Coordinator should run `Distributed` engine with grid
+//! of `Life` cells defined here and Moore neighborhood.
+
+#![feature(custom_derive, plugin)]
+#![plugin(serde_macros)]
+
+extern crate celly;
+extern crate serde;
+
+use std::env;
+
+use celly::traits::Cell;
+use celly::traits::Coord;
+use celly::traits::Neighbors;
+use celly::engine::distributed;
+use celly::grid::nhood::MooreNhood;
+use celly::grid::EmptyState;
+
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum LifeState {
+    Dead,
+    Alive,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Life {
+    state: LifeState,
+    coord: (i32, i32),
+}
+
+
+impl Cell for Life {
+    type Coord = (i32, i32);
+    type State = EmptyState;
+
+    fn update<'a, I>(&'a mut self, old: &'a Self, neighbors: I, _: &Self::State)
+        where I: Neighbors<'a, Self>,
+    {
+        let alive = neighbors.filter(|n| n.map_or(false, |n| n.state == LifeState::Alive))
+            .count();
+
+        self.state = match (&old.state, alive) {
+            (&LifeState::Alive, 2) | (_, 3) => LifeState::Alive,
+            _ => LifeState::Dead,
+        };
+    }
+
+    fn with_coord<C: Coord>(coord: C) -> Self {
+        Life {
+            state: LifeState::Dead,
+            coord: (coord.x(), coord.y()),
+        }
+    }
+
+    fn coord(&self) -> &Self::Coord { &self.coord }
+
+    fn set_coord<C: Coord>(&mut self, coord: &C) { self.coord = (coord.x(), coord.y()); }
+}
+
+
+fn main() {
+    let address = env::args().nth(1).expect("Address of coordinator should be given.");
+
+    distributed::work::<Life, _, EmptyState, _>(&address[..], MooreNhood::new(), 1).unwrap();
+}
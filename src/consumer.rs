@@ -0,0 +1,140 @@
+//! Helpers to attach several consumers to one engine
+//! and to call them not on every generation.
+//!
+//! Tuple of consumers (up to 6) is consumer itself, so
+//! engine could feed them all:
+//!
+//! ```ignore
+//! let consumers = (renderer, Every::new(100, logger), At::new(vec![1000], writer));
+//! let mut engine = Sequential::new(grid, consumers);
+//! ```
+
+use std::cmp;
+
+use traits::Consumer;
+use traits::Grid;
+use engine::Context;
+
+
+/// Calls wrapped consumer every `period` generations.
+/// If engine skips generations (like `HashLife` does),
+/// consumer is called once the next multiple of
+/// `period` has been passed.
+pub struct Every<Con: Consumer> {
+    consumer: Con,
+    period: u64,
+    next: u64,
+}
+
+impl<Con: Consumer> Every<Con> {
+    /// Wraps consumer which is called every
+    /// `period` generations only.
+    pub fn new(period: u64, consumer: Con) -> Self {
+        let period = cmp::max(period, 1);
+
+        Every {
+            consumer: consumer,
+            period: period,
+            next: period,
+        }
+    }
+
+    /// Returns wrapped consumer.
+    pub fn consumer(&self) -> &Con { &self.consumer }
+}
+
+impl<Con: Consumer> Consumer for Every<Con> {
+    type Cell = Con::Cell;
+
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, context: &Context) {
+        if context.generation >= self.next {
+            self.next = (context.generation / self.period + 1) * self.period;
+            self.consumer.consume(grid, context);
+        }
+    }
+}
+
+
+/// Calls wrapped consumer on given generations only.
+/// If engine skips generations, consumer is called
+/// once any of given generations has been passed.
+pub struct At<Con: Consumer> {
+    consumer: Con,
+    at: Vec<u64>,
+    next: usize,
+}
+
+impl<Con: Consumer> At<Con> {
+    /// Wraps consumer which is called on given generations only.
+    pub fn new(mut at: Vec<u64>, consumer: Con) -> Self {
+        at.sort();
+        at.dedup();
+
+        At {
+            consumer: consumer,
+            at: at,
+            next: 0,
+        }
+    }
+
+    /// Returns wrapped consumer.
+    pub fn consumer(&self) -> &Con { &self.consumer }
+}
+
+impl<Con: Consumer> Consumer for At<Con> {
+    type Cell = Con::Cell;
+
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, context: &Context) {
+
+        let passed = self.at[self.next..]
+            .iter()
+            .take_while(|&&generation| generation <= context.generation)
+            .count();
+
+        if passed > 0 {
+            self.next += passed;
+            self.consumer.consume(grid, context);
+        }
+    }
+}
+
+
+/// Consumers of the same type are called in order.
+impl<Con: Consumer> Consumer for Vec<Con> {
+    type Cell = Con::Cell;
+
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, context: &Context) {
+        for consumer in self.iter_mut() {
+            consumer.consume(grid, context);
+        }
+    }
+}
+
+
+/// Implements `Consumer` for tuple of consumers of the same
+/// cell. They are called in order.
+macro_rules! consumer_tuple {
+    ($first:ident $(, $rest:ident)*) => {
+        #[allow(non_snake_case)]
+        impl<$first: Consumer $(, $rest: Consumer<Cell = $first::Cell>)*> Consumer
+            for ($first, $($rest),*)
+        {
+            type Cell = $first::Cell;
+
+            fn consume<G>(&mut self, grid: &mut G, context: &Context)
+                where G: Grid<Cell = Self::Cell>,
+            {
+                let (ref mut $first, $(ref mut $rest),*) = *self;
+
+                $first.consume(grid, context);
+                $($rest.consume(grid, context);)*
+            }
+        }
+    };
+}
+
+consumer_tuple!(A, B);
+consumer_tuple!(A, B, C);
+consumer_tuple!(A, B, C, D);
+consumer_tuple!(A, B, C, D, E);
+consumer_tuple!(A, B, C, D, E, F);
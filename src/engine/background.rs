@@ -0,0 +1,278 @@
+//! Engine which runs evolution on its own thread.
+//! Other thread controls it with commands and
+//! receives frames back.
+
+use std::mem;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::Sender;
+use std::sync::mpsc::SyncSender;
+use std::sync::mpsc::TryRecvError;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use traits::Cell;
+use traits::Consumer;
+use traits::Coord;
+use traits::Engine;
+use traits::Grid;
+use engine::Context;
+use engine::Sequential;
+
+
+/// Control messages accepted by `Background` engine.
+pub enum Command<C> {
+    /// Stops running evolution.
+    Pause,
+    /// Runs evolution until `Pause` or `Stop`.
+    Resume,
+    /// Runs one generation. Useful while paused.
+    Step,
+    /// Sets delay between generations while running.
+    Speed(Duration),
+    /// Changes cells of grid. Frame is published
+    /// afterwards, so changes can be seen while paused.
+    SetCells(Vec<C>),
+    /// Stops worker thread.
+    Stop,
+}
+
+
+/// Copy of grid published by worker thread. Frame implements
+/// `Grid`, so usual consumers can be fed with it. Frame
+/// does not evolve, cells changed through `set_cells` are sent
+/// back to worker by `Background`.
+pub struct Frame<C: Cell> {
+    context: Context,
+    cells: Vec<C>,
+    state: C::State,
+    size: (i32, i32, i32),
+    edits: Vec<C>,
+}
+
+
+impl<C: Cell + Clone> Frame<C>
+    where C::State: Clone,
+{
+    fn new<G: Grid<Cell = C>>(grid: &G, context: Context) -> Self {
+        let size = grid.size();
+
+        Frame {
+            context: context,
+            cells: grid.cells().to_vec(),
+            state: grid.state().clone(),
+            size: (size.x(), size.y(), size.z()),
+            edits: Vec::new(),
+        }
+    }
+
+    /// Describes step frame has been published after.
+    pub fn context(&self) -> &Context { &self.context }
+}
+
+
+impl<C: Cell> Grid for Frame<C> {
+    type Cell = C;
+    type Coord = (i32, i32, i32);
+
+    /// Frame does not evolve.
+    fn update(&mut self) {}
+
+    fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { &self.state }
+
+    /// Changes only local copy of state.
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) { self.state = state; }
+
+    fn cells(&self) -> &[Self::Cell] { &self.cells }
+
+    fn size(&self) -> Self::Coord { self.size }
+
+    /// Cells are kept until `Background` sends them to worker.
+    fn set_cells(&mut self, new_cells: Vec<Self::Cell>) { self.edits.extend(new_cells); }
+
+    fn changed(&self) -> Option<usize> { self.context.changed }
+}
+
+
+/// Consumer which publishes frames from worker thread.
+struct Publisher<C: Cell> {
+    frames: SyncSender<Frame<C>>,
+}
+
+impl<C: Cell + Clone> Consumer for Publisher<C>
+    where C::State: Clone,
+{
+    type Cell = C;
+
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, context: &Context) {
+        // Nobody listens if receiver is gone, worker
+        // will be stopped soon anyway.
+        let _ = self.frames.send(Frame::new(grid, context.clone()));
+    }
+}
+
+
+/// Count of published frames which have not been
+/// consumed yet. Worker waits when there are more.
+pub const FRAMES: usize = 4;
+
+
+/// Engine which owns grid on background thread. Grid
+/// is paused at start. Worker thread is stopped when
+/// engine is dropped. Only a few frames are queued,
+/// so worker waits until they are consumed with
+/// `poll` or `wait` and commands wait too.
+pub struct Background<G: Grid> {
+    commands: Sender<Command<G::Cell>>,
+    frames: Receiver<Frame<G::Cell>>,
+    worker: Option<JoinHandle<G>>,
+}
+
+
+impl<G> Background<G>
+    where G: Grid + Send + 'static,
+          G::Cell: Clone + Send + 'static,
+          <G::Cell as Cell>::State: Clone + Send,
+{
+    /// Moves grid to new thread.
+    pub fn spawn(grid: G) -> Self {
+
+        let (commands, commands_rx) = mpsc::channel();
+        let (frames_tx, frames) = mpsc::sync_channel(FRAMES);
+
+        let worker = thread::spawn(move || work(grid, commands_rx, frames_tx));
+
+        Background {
+            commands: commands,
+            frames: frames,
+            worker: Some(worker),
+        }
+    }
+
+    /// Sends command to worker.
+    pub fn send(&self, command: Command<G::Cell>) {
+        // Worker is gone only if it has panicked,
+        // `stop` will report it.
+        let _ = self.commands.send(command);
+    }
+
+    /// Feeds consumer with all published frames without
+    /// waiting. Returns count of frames consumed.
+    pub fn poll<Con>(&mut self, consumer: &mut Con) -> usize
+        where Con: Consumer<Cell = G::Cell>,
+    {
+        let mut count = 0;
+
+        while let Ok(frame) = self.frames.try_recv() {
+            self.feed(frame, consumer);
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Waits for the next frame and feeds consumer with it.
+    /// Returns `false` if worker is stopped.
+    pub fn wait<Con>(&mut self, consumer: &mut Con) -> bool
+        where Con: Consumer<Cell = G::Cell>,
+    {
+        match self.frames.recv() {
+            Ok(frame) => {
+                self.feed(frame, consumer);
+                true
+            },
+            Err(_) => false,
+        }
+    }
+
+    /// Stops worker and returns grid back.
+    pub fn stop(mut self) -> G {
+        self.send(Command::Stop);
+        // Worker may wait for room in queue of frames,
+        // so frames are dropped until it is gone.
+        while self.frames.recv().is_ok() {}
+
+        let worker = self.worker.take().expect("worker is joined only once");
+        worker.join().expect("worker thread panicked")
+    }
+
+    fn feed<Con>(&self, mut frame: Frame<G::Cell>, consumer: &mut Con)
+        where Con: Consumer<Cell = G::Cell>,
+    {
+        let context = frame.context.clone();
+        consumer.consume(&mut frame, &context);
+
+        if !frame.edits.is_empty() {
+            let edits = mem::replace(&mut frame.edits, Vec::new());
+            self.send(Command::SetCells(edits));
+        }
+    }
+}
+
+
+impl<G: Grid> Drop for Background<G> {
+    fn drop(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            let _ = self.commands.send(Command::Stop);
+            while self.frames.recv().is_ok() {}
+            let _ = worker.join();
+        }
+    }
+}
+
+
+/// Loop of worker thread.
+fn work<G>(grid: G,
+           commands: Receiver<Command<G::Cell>>,
+           frames: SyncSender<Frame<G::Cell>>)
+           -> G
+    where G: Grid,
+          G::Cell: Clone,
+          <G::Cell as Cell>::State: Clone,
+{
+    let publisher = Publisher { frames: frames.clone() };
+    let mut engine = Sequential::new(grid, publisher);
+
+    let mut running = false;
+    let mut delay = Duration::from_millis(0);
+
+    loop {
+        let command = if running {
+            match commands.try_recv() {
+                Ok(command) => Some(command),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => break,
+            }
+        } else {
+            match commands.recv() {
+                Ok(command) => Some(command),
+                Err(_) => break,
+            }
+        };
+
+        match command {
+            Some(Command::Pause) => running = false,
+            Some(Command::Resume) => running = true,
+            Some(Command::Step) => engine.run_times(1),
+            Some(Command::Speed(new_delay)) => delay = new_delay,
+            Some(Command::SetCells(cells)) => {
+                engine.grid_mut().set_cells(cells);
+
+                let context = Context {
+                    generation: engine.generation(),
+                    duration: Duration::from_millis(0),
+                    changed: None,
+                };
+                let _ = frames.send(Frame::new(engine.grid(), context));
+            },
+            Some(Command::Stop) => break,
+            None => {
+                engine.run_times(1);
+                thread::sleep(delay);
+            },
+        }
+    }
+
+    engine.into_grid()
+}
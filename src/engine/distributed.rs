@@ -0,0 +1,510 @@
+//! Engine which splits `TwodimGrid` into strips of rows
+//! and evolves every strip in separate worker process.
+//!
+//! Coordinator (`Distributed`) accepts connections of
+//! workers, gives every worker its strip and relays halo
+//! rows between them on every generation. Worker is started
+//! with `work` in any process which knows cell type and
+//! neighborhood of grid (see `examples/distributed_worker.rs`):
+//!
+//! ```ignore
+//! // Worker process.
+//! distributed::work::<Life, _, EmptyState, _>("127.0.0.1:4000", MooreNhood::new(), 4).unwrap();
+//!
+//! // Coordinator process.
+//! let listener = TcpListener::bind("127.0.0.1:4000").unwrap();
+//! let mut engine = Distributed::new(grid, &listener, 4, consumer).unwrap();
+//! engine.run_times(1000);
+//! ```
+
+use std::cmp;
+use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::mem;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs;
+use std::time::Duration;
+use std::time::Instant;
+
+use bincode;
+use bincode::SizeLimit;
+use serde::{Deserialize, Serialize};
+
+use traits::Cell;
+use traits::Consumer;
+use traits::Coord;
+use traits::Engine;
+use traits::EvolutionState;
+use traits::Grid;
+use traits::Nhood;
+use engine::Context;
+use engine::Stop;
+use engine::StopCondition;
+use grid::UpdateMode;
+use grid::twodim::Checkpoint;
+use grid::twodim::GridCoord;
+use grid::twodim::TwodimGrid;
+
+
+/// Messages between coordinator and workers.
+#[derive(Serialize, Deserialize)]
+enum Message<C, Es> {
+    /// Whole grid and strip of worker
+    /// with rows it should export.
+    Init {
+        checkpoint: Checkpoint<C, Es>,
+        start: u32,
+        end: u32,
+        exports: Vec<u32>,
+    },
+    /// Halo rows for the next step.
+    Step(Vec<C>),
+    /// Cells of exported rows after step.
+    Exports(Vec<C>),
+    /// Cells changed from outside.
+    SetCells(Vec<C>),
+    /// Evolution state changed from outside.
+    SetState(Es),
+    /// Request for cells of strip.
+    Gather,
+    /// Cells of strip and evolution state.
+    Strip(Vec<C>, Es),
+    /// Worker should exit.
+    Stop,
+}
+
+
+/// Connection to the other side.
+struct Link {
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+}
+
+impl Link {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        try!(stream.set_nodelay(true));
+        let reader = BufReader::new(try!(stream.try_clone()));
+
+        Ok(Link {
+            reader: reader,
+            writer: BufWriter::new(stream),
+        })
+    }
+
+    fn send<C, Es>(&mut self, message: &Message<C, Es>) -> io::Result<()>
+        where C: Cell,
+              Es: EvolutionState + Clone + Serialize,
+    {
+        try!(bincode::serde::serialize_into(&mut self.writer, message, SizeLimit::Infinite)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e)));
+        self.writer.flush()
+    }
+
+    fn recv<C, Es>(&mut self) -> io::Result<Message<C, Es>>
+        where C: Cell,
+              Es: EvolutionState + Clone + Deserialize,
+    {
+        bincode::serde::deserialize_from(&mut self.reader, SizeLimit::Infinite)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+}
+
+
+fn unexpected() -> io::Error { io::Error::new(io::ErrorKind::InvalidData, "unexpected message") }
+
+
+/// Runs worker: connects to coordinator at `address` and
+/// evolves strip it is given until coordinator stops it.
+/// Neighborhood should be the same as neighborhood of grid
+/// given to coordinator.
+pub fn work<C, N, Es, A>(address: A, nhood: N, threads: u32) -> io::Result<()>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = GridCoord>,
+          Es: EvolutionState + Clone + Send + Serialize + Deserialize,
+          A: ToSocketAddrs,
+{
+    let mut link = try!(Link::new(try!(TcpStream::connect(address))));
+
+    let (mut grid, start, end, exports) = match try!(link.recv()) {
+        Message::Init { checkpoint, start, end, exports } => {
+            let grid = try!(TwodimGrid::from_checkpoint(checkpoint, nhood, threads)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)));
+            (grid, start, end, exports)
+        },
+        _ => return Err(unexpected()),
+    };
+
+    loop {
+        match try!(link.recv::<C, Es>()) {
+            Message::Step(halo) => {
+                grid.set_cells(halo);
+                grid.update_rows(start, end);
+
+                let cells = exports.iter()
+                    .flat_map(|&row| rows(&grid, row, row + 1).iter().cloned())
+                    .collect();
+                try!(link.send::<C, Es>(&Message::Exports(cells)));
+            },
+            Message::SetCells(cells) => grid.set_cells(cells),
+            Message::SetState(state) => grid.set_state(state),
+            Message::Gather => {
+                let cells = rows(&grid, start, end).to_vec();
+                let state = grid.state().clone();
+                try!(link.send(&Message::Strip(cells, state)));
+            },
+            Message::Stop => return Ok(()),
+            _ => return Err(unexpected()),
+        }
+    }
+}
+
+
+/// Returns cells of rows from `start` to `end`.
+fn rows<G: Grid<Coord = GridCoord>>(grid: &G, start: u32, end: u32) -> &[G::Cell] {
+    let cols = grid.size().x() as usize;
+    &grid.cells()[start as usize * cols..end as usize * cols]
+}
+
+
+/// Worker as coordinator sees it.
+struct Worker<C> {
+    link: Link,
+    start: u32,
+    end: u32,
+    halo: Vec<C>,
+}
+
+
+/// Grid given to consumer. Cells and state it changes
+/// are remembered to be sent to workers.
+struct Master<'a, C, N, Es>
+    where C: Cell<State = Es> + Clone + 'a,
+          N: Nhood<Coord = GridCoord> + 'a,
+          Es: EvolutionState + Send + 'a,
+{
+    grid: &'a mut TwodimGrid<C, N, Es>,
+    edits: Vec<C>,
+    state_changed: bool,
+}
+
+impl<'a, C, N, Es> Grid for Master<'a, C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = GridCoord>,
+          Es: EvolutionState + Send,
+{
+    type Cell = C;
+    type Coord = GridCoord;
+
+    /// Grid is evolved by workers.
+    fn update(&mut self) {}
+
+    fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { self.grid.state() }
+
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) {
+        self.state_changed = true;
+        self.grid.set_state(state);
+    }
+
+    fn cells(&self) -> &[Self::Cell] { self.grid.cells() }
+
+    fn size(&self) -> Self::Coord { self.grid.size() }
+
+    fn set_cells(&mut self, new_cells: Vec<Self::Cell>) {
+        self.edits.extend(new_cells.iter().cloned());
+        self.grid.set_cells(new_cells);
+    }
+
+    fn changed(&self) -> Option<usize> { None }
+}
+
+
+/// Coordinator of distributed evolution. Grid is split into
+/// strips of rows, one strip per worker. On every generation
+/// workers send rows other strips depend on and coordinator
+/// relays them. Like `Sequential`, `run_times` feeds consumer
+/// on every generation, so whole grid is gathered every time.
+/// `run_batched` gathers grid only after all generations.
+///
+/// Connection errors are not recoverable, engine panics on them.
+pub struct Distributed<C, N, Es, Con>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = GridCoord>,
+          Es: EvolutionState + Clone + Send + Serialize + Deserialize,
+          Con: Consumer<Cell = C>,
+{
+    grid: TwodimGrid<C, N, Es>,
+    workers: Vec<Worker<C>>,
+    /// Workers which need every row.
+    routes: Vec<Vec<usize>>,
+    consumer: Con,
+    generation: u64,
+}
+
+
+impl<C, N, Es, Con> Distributed<C, N, Es, Con>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = GridCoord>,
+          Es: EvolutionState + Clone + Send + Serialize + Deserialize,
+          Con: Consumer<Cell = C>,
+{
+    /// Accepts connections of `workers` workers on listener
+    /// and gives each of them its strip of grid. Strips are
+    /// updated independently, so grid should be updated
+    /// synchronously. Workers use `Cell::update`, so grid
+    /// should have empty pipeline. Returns `InvalidInput`
+    /// error if there are no workers or grid has no rows.
+    pub fn new(grid: TwodimGrid<C, N, Es>,
+               listener: &TcpListener,
+               workers: u32,
+               consumer: Con)
+               -> io::Result<Self> {
+
+        assert!(*grid.update_mode() == UpdateMode::Synchronous,
+                "Distributed grid should be updated synchronously.");
+        assert!(grid.pipeline().is_empty(),
+                "Distributed grid should be updated with `Cell::update`.");
+
+        let rows = grid.size().y() as u32;
+        if workers == 0 || rows == 0 {
+            let message = "distributed grid should have rows and workers";
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+        let workers = cmp::min(workers, rows);
+
+        let strips: Vec<_> = (0..workers)
+            .map(|i| (i * rows / workers, (i + 1) * rows / workers))
+            .collect();
+
+        let mut routes = vec![Vec::new(); rows as usize];
+        for (i, &(start, end)) in strips.iter().enumerate() {
+            for row in grid.dependencies(start, end) {
+                routes[row as usize].push(i);
+            }
+        }
+
+        let mut engine = Distributed {
+            grid: grid,
+            workers: Vec::with_capacity(workers as usize),
+            routes: routes,
+            consumer: consumer,
+            generation: 0,
+        };
+
+        for &(start, end) in &strips {
+            let (stream, _) = try!(listener.accept());
+            let mut link = try!(Link::new(stream));
+
+            let exports = (start..end)
+                .filter(|&row| !engine.routes[row as usize].is_empty())
+                .collect();
+            try!(link.send(&Message::Init {
+                checkpoint: engine.grid.checkpoint(0),
+                start: start,
+                end: end,
+                exports: exports,
+            }));
+
+            engine.workers.push(Worker {
+                link: link,
+                start: start,
+                end: end,
+                halo: Vec::new(),
+            });
+        }
+
+        engine.prepare_halos();
+
+        Ok(engine)
+    }
+
+    /// Returns count of generations passed.
+    pub fn generation(&self) -> u64 { self.generation }
+
+    /// Returns grid as it was gathered last time.
+    pub fn grid(&self) -> &TwodimGrid<C, N, Es> { &self.grid }
+
+    /// Runs evolution fixed number of times, then gathers
+    /// grid and calls consumer once, so duration in context
+    /// covers all generations.
+    pub fn run_batched(&mut self, times: u64) {
+        self.run(times).expect("worker connection failed");
+    }
+
+    /// Takes halo rows from grid of coordinator.
+    fn prepare_halos(&mut self) {
+        for worker in &mut self.workers {
+            worker.halo.clear();
+        }
+
+        for (row, destinations) in self.routes.iter().enumerate() {
+            for &destination in destinations {
+                let cells = rows(&self.grid, row as u32, row as u32 + 1);
+                self.workers[destination].halo.extend(cells.iter().cloned());
+            }
+        }
+    }
+
+    fn step(&mut self) -> io::Result<()> {
+
+        for worker in &mut self.workers {
+            let halo = mem::replace(&mut worker.halo, Vec::new());
+            try!(worker.link.send::<C, Es>(&Message::Step(halo)));
+        }
+
+        let cols = self.grid.size().x();
+        let mut exported = Vec::with_capacity(self.workers.len());
+
+        for worker in &mut self.workers {
+            match try!(worker.link.recv::<C, Es>()) {
+                Message::Exports(cells) => exported.push(cells),
+                _ => return Err(unexpected()),
+            }
+        }
+
+        for cell in exported.into_iter().flat_map(|cells| cells.into_iter()) {
+            let row = cell.coord().y() as usize;
+            debug_assert!(cell.coord().x() < cols);
+
+            for &destination in &self.routes[row] {
+                self.workers[destination].halo.push(cell.clone());
+            }
+        }
+
+        self.generation += 1;
+
+        Ok(())
+    }
+
+    /// Collects strips of all workers into grid of coordinator.
+    fn gather(&mut self) -> io::Result<()> {
+
+        for worker in &mut self.workers {
+            try!(worker.link.send::<C, Es>(&Message::Gather));
+        }
+
+        for worker in &mut self.workers {
+            match try!(worker.link.recv()) {
+                Message::Strip(cells, state) => {
+                    self.grid.set_cells(cells);
+                    self.grid.set_state(state);
+                },
+                _ => return Err(unexpected()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Feeds consumer and sends cells it has changed to workers.
+    fn consume(&mut self, duration: Duration) -> io::Result<()> {
+
+        let context = Context {
+            generation: self.generation,
+            duration: duration,
+            changed: None,
+        };
+
+        let (edits, state_changed) = {
+            let mut master = Master {
+                grid: &mut self.grid,
+                edits: Vec::new(),
+                state_changed: false,
+            };
+            self.consumer.consume(&mut master, &context);
+            (master.edits, master.state_changed)
+        };
+
+        if state_changed {
+            let message = Message::SetState(self.grid.state().clone());
+            for worker in &mut self.workers {
+                try!(worker.link.send::<C, Es>(&message));
+            }
+        }
+
+        if edits.is_empty() {
+            return Ok(());
+        }
+
+        for worker in &mut self.workers {
+            let cells = edits.iter()
+                .filter(|cell| {
+                    let row = cell.coord().y() as u32;
+                    row >= worker.start && row < worker.end
+                })
+                .cloned()
+                .collect();
+            try!(worker.link.send::<C, Es>(&Message::SetCells(cells)));
+        }
+
+        // Halos should see changes too.
+        self.prepare_halos();
+
+        Ok(())
+    }
+
+    fn run(&mut self, times: u64) -> io::Result<()> {
+        if times == 0 {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+
+        for _ in 0..times {
+            try!(self.step());
+        }
+        try!(self.gather());
+
+        self.consume(start.elapsed())
+    }
+}
+
+
+impl<C, N, Es, Con> Engine for Distributed<C, N, Es, Con>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = GridCoord>,
+          Es: EvolutionState + Clone + Send + Serialize + Deserialize,
+          Con: Consumer<Cell = C>,
+{
+    type Grid = TwodimGrid<C, N, Es>;
+
+    /// Gathers grid on every generation to feed consumer.
+    fn run_times(&mut self, times: u64) {
+        for _ in 0..times {
+            self.run_batched(1);
+        }
+    }
+
+    /// Gathers grid on every generation to check condition
+    /// and feed consumer.
+    fn run_until<S>(&mut self, mut condition: S) -> Stop
+        where S: StopCondition<Self::Grid>,
+    {
+        loop {
+            if let Some(reason) = condition.check(&self.grid, self.generation) {
+                return Stop {
+                    reason: reason,
+                    generation: self.generation,
+                };
+            }
+
+            self.run_times(1);
+        }
+    }
+}
+
+
+impl<C, N, Es, Con> Drop for Distributed<C, N, Es, Con>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = GridCoord>,
+          Es: EvolutionState + Clone + Send + Serialize + Deserialize,
+          Con: Consumer<Cell = C>,
+{
+    fn drop(&mut self) {
+        for worker in &mut self.workers {
+            let _ = worker.link.send::<C, Es>(&Message::Stop);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Engine which advances `HashLifeGrid` with
+//! superspeed steps.
+
+use std::time::Duration;
+use std::time::Instant;
+
+use traits::Consumer;
+use traits::Grid;
+use traits::Engine;
+use engine::Context;
+use engine::Stop;
+use engine::StopCondition;
+use grid::hashlife::BinaryCell;
+use grid::hashlife::HashLifeGrid;
+
+
+/// Engine for `HashLifeGrid`. Like `Sequential`, `run_times`
+/// and `run_until` advance grid one generation at a time and
+/// call consumer after every generation. `run_batched` uses
+/// superspeed steps to advance grid by many generations at
+/// once and calls consumer only after that.
+pub struct HashLife<C: BinaryCell, Con: Consumer<Cell = C>> {
+    grid: HashLifeGrid<C>,
+    consumer: Con,
+}
+
+impl<C: BinaryCell, Con: Consumer<Cell = C>> HashLife<C, Con> {
+    /// Constructs engine with given grid and consumer.
+    pub fn new(grid: HashLifeGrid<C>, consumer: Con) -> Self {
+        HashLife {
+            grid: grid,
+            consumer: consumer,
+        }
+    }
+
+    /// Advances grid by given number of generations at once,
+    /// then calls consumer, so duration in context covers
+    /// all of them.
+    pub fn run_batched(&mut self, times: u64) {
+        if times == 0 {
+            return;
+        }
+
+        let start = Instant::now();
+        self.grid.advance(times);
+        let duration = start.elapsed();
+
+        self.consume(duration);
+    }
+
+    fn consume(&mut self, duration: Duration) {
+        let context = Context {
+            generation: self.grid.generation(),
+            duration: duration,
+            changed: self.grid.changed(),
+        };
+        self.consumer.consume(&mut self.grid, &context);
+    }
+}
+
+
+impl<C: BinaryCell, Con: Consumer<Cell = C>> Engine for HashLife<C, Con> {
+    type Grid = HashLifeGrid<C>;
+
+    fn run_times(&mut self, times: u64) {
+        for _ in 0..times {
+            self.run_batched(1);
+        }
+    }
+
+    fn run_until<S>(&mut self, mut condition: S) -> Stop
+        where S: StopCondition<Self::Grid>,
+    {
+        loop {
+            let generation = self.grid.generation();
+
+            if let Some(reason) = condition.check(&self.grid, generation) {
+                return Stop {
+                    reason: reason,
+                    generation: generation,
+                };
+            }
+
+            self.run_batched(1);
+        }
+    }
+}
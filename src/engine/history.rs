@@ -0,0 +1,337 @@
+//! Engine which remembers past states of grid
+//! and can rewind evolution.
+
+use std::collections::VecDeque;
+use std::mem;
+
+use traits::Cell;
+use traits::Consumer;
+use traits::Grid;
+use traits::Engine;
+use engine::Context;
+use engine::Sequential;
+use engine::Stop;
+use engine::StopCondition;
+
+
+/// State of grid at some generation.
+struct Snapshot<C: Cell> {
+    generation: u64,
+    cells: Vec<C>,
+    state: C::State,
+}
+
+
+/// Default limit of keyframes count.
+const MAX_KEYFRAMES: usize = 64;
+
+
+/// Wrapper around `Sequential` which keeps snapshots
+/// of recent generations in bounded ring buffer. Besides
+/// them every `interval`-th generation is kept as keyframe.
+/// Rewinding to generation older than ring buffer restores
+/// the nearest keyframe and re-simulates forward from it.
+///
+/// At most 64 keyframes are kept by default. When there are
+/// more of them, every second keyframe is dropped and interval
+/// is doubled, so keyframes cover whole evolution sparser.
+///
+/// Snapshots are restored with `Grid::replace_cells`.
+/// Re-simulation does not call consumer, so it can't repeat
+/// edits consumer has made. Instead generation consumer has
+/// edited becomes the first keyframe and older keyframes are
+/// dropped: generations before edit can be reached only
+/// through ring buffer after that.
+pub struct History<C, G, Con>
+    where C: Cell + Clone,
+          C::State: Clone,
+          G: Grid<Cell = C>,
+          Con: Consumer<Cell = C>,
+{
+    engine: Sequential<C, G, Tracking<Con>>,
+    recent: VecDeque<Snapshot<C>>,
+    keyframes: Vec<Snapshot<C>>,
+    capacity: usize,
+    interval: u64,
+    max_keyframes: usize,
+}
+
+
+impl<C, G, Con> History<C, G, Con>
+    where C: Cell + Clone,
+          C::State: Clone,
+          G: Grid<Cell = C>,
+          Con: Consumer<Cell = C>,
+{
+    /// Wraps engine. Ring buffer keeps `capacity` last
+    /// generations, keyframes are taken every `interval`
+    /// generations starting from the current one.
+    pub fn new(engine: Sequential<C, G, Con>, capacity: usize, interval: u64) -> Self {
+
+        let generation = engine.generation();
+        let (grid, consumer) = engine.into_parts();
+        let tracking = Tracking {
+            consumer: consumer,
+            edited: false,
+        };
+        let mut engine = Sequential::new(grid, tracking);
+        engine.set_generation(generation);
+
+        let mut history = History {
+            engine: engine,
+            recent: VecDeque::with_capacity(capacity),
+            keyframes: Vec::new(),
+            capacity: capacity,
+            interval: interval,
+            max_keyframes: MAX_KEYFRAMES,
+        };
+
+        let keyframe = history.snapshot();
+        history.keyframes.push(keyframe);
+        history.record();
+
+        history
+    }
+
+    /// Returns count of generations passed.
+    pub fn generation(&self) -> u64 { self.engine.generation() }
+
+    /// Getter for grid.
+    pub fn grid(&self) -> &G { self.engine.grid() }
+
+    /// Returns count of kept keyframes.
+    pub fn keyframes_count(&self) -> usize { self.keyframes.len() }
+
+    /// Limits count of kept keyframes. Limit should be at least 2.
+    pub fn set_max_keyframes(&mut self, count: usize) {
+        assert!(count >= 2, "At least 2 keyframes should be kept.");
+
+        self.max_keyframes = count;
+        while self.keyframes.len() > self.max_keyframes {
+            self.thin_keyframes();
+        }
+    }
+
+    /// Steps back by `steps` generations
+    /// (but not beyond the first keyframe).
+    pub fn step_back(&mut self, steps: u64) {
+        let target = self.generation().saturating_sub(steps);
+        self.seek(target);
+    }
+
+    /// Brings grid to given generation. Later generations
+    /// are run as usual, earlier ones are restored from
+    /// snapshots. Generation older than the first keyframe
+    /// is replaced with the first keyframe.
+    pub fn seek(&mut self, generation: u64) {
+
+        let current = self.generation();
+
+        if generation >= current {
+            self.run_times(generation - current);
+            return;
+        }
+
+        // Snapshots of rewound generations are invalid now.
+        while self.recent.back().map_or(false, |snapshot| snapshot.generation > generation) {
+            self.recent.pop_back();
+        }
+        while self.keyframes.len() > 1 &&
+              self.keyframes.last().map_or(false, |keyframe| keyframe.generation > generation) {
+            self.keyframes.pop();
+        }
+
+        let keyframe = self.keyframes.last().map_or(0, |keyframe| keyframe.generation);
+        let recent = self.recent.back().map_or(false, |snapshot| snapshot.generation >= keyframe);
+
+        // Starting from the nearest snapshot.
+        if recent {
+            let snapshot = self.recent.pop_back().expect("snapshot is checked");
+            self.restore(&snapshot);
+            self.recent.push_back(snapshot);
+        } else {
+            let keyframe = self.keyframes.pop().expect("first keyframe is always kept");
+            self.restore(&keyframe);
+            self.keyframes.push(keyframe);
+
+            self.recent.clear();
+            self.record();
+        }
+
+        while self.generation() < generation {
+            self.engine.grid_mut().update();
+
+            let next = self.generation() + 1;
+            self.engine.set_generation(next);
+
+            self.record();
+        }
+    }
+
+    fn step(&mut self) {
+        self.engine.run_times(1);
+
+        if mem::replace(&mut self.engine.consumer_mut().edited, false) {
+            // Keyframes before edit would be re-simulated without it.
+            self.keyframes.clear();
+            let keyframe = self.snapshot();
+            self.keyframes.push(keyframe);
+        }
+
+        self.record();
+    }
+
+    /// Pushes current state to ring buffer and
+    /// keyframes if it is time for keyframe.
+    fn record(&mut self) {
+
+        let generation = self.generation();
+        let first = self.keyframes[0].generation;
+        let last = self.keyframes[self.keyframes.len() - 1].generation;
+
+        if self.interval != 0 && generation > last && (generation - first) % self.interval == 0 {
+            let keyframe = self.snapshot();
+            self.keyframes.push(keyframe);
+
+            if self.keyframes.len() > self.max_keyframes {
+                self.thin_keyframes();
+            }
+        }
+
+        if self.capacity == 0 {
+            return;
+        }
+        if self.recent.len() == self.capacity {
+            self.recent.pop_front();
+        }
+
+        let snapshot = self.snapshot();
+        self.recent.push_back(snapshot);
+    }
+
+    /// Drops every second keyframe except the first one.
+    /// Kept keyframes are `2 * interval` apart then.
+    fn thin_keyframes(&mut self) {
+        let keyframes = mem::replace(&mut self.keyframes, Vec::new());
+
+        self.keyframes = keyframes.into_iter()
+            .enumerate()
+            .filter(|&(i, _)| i % 2 == 0)
+            .map(|(_, keyframe)| keyframe)
+            .collect();
+        self.interval *= 2;
+    }
+
+    fn snapshot(&self) -> Snapshot<C> {
+        let grid = self.engine.grid();
+
+        Snapshot {
+            generation: self.generation(),
+            cells: grid.cells().to_vec(),
+            state: grid.state().clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot<C>) {
+        {
+            let grid = self.engine.grid_mut();
+            grid.replace_cells(snapshot.cells.clone());
+            grid.set_state(snapshot.state.clone());
+        }
+
+        self.engine.set_generation(snapshot.generation);
+    }
+}
+
+
+/// Consumer wrapper which notices edits of grid.
+struct Tracking<Con> {
+    consumer: Con,
+    edited: bool,
+}
+
+impl<Con: Consumer> Consumer for Tracking<Con> {
+    type Cell = Con::Cell;
+
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, context: &Context) {
+        let mut tracked = Tracked {
+            grid: grid,
+            edited: false,
+        };
+        self.consumer.consume(&mut tracked, context);
+        self.edited = self.edited || tracked.edited;
+    }
+}
+
+
+/// Grid given to consumer. Remembers whether it has been changed.
+struct Tracked<'a, G: Grid + 'a> {
+    grid: &'a mut G,
+    edited: bool,
+}
+
+impl<'a, G: Grid> Grid for Tracked<'a, G> {
+    type Cell = G::Cell;
+    type Coord = G::Coord;
+
+    fn update(&mut self) {
+        self.edited = true;
+        self.grid.update();
+    }
+
+    fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { self.grid.state() }
+
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) {
+        self.edited = true;
+        self.grid.set_state(state);
+    }
+
+    fn cells(&self) -> &[Self::Cell] { self.grid.cells() }
+
+    fn size(&self) -> Self::Coord { self.grid.size() }
+
+    fn set_cells(&mut self, new_cells: Vec<Self::Cell>) {
+        self.edited = true;
+        self.grid.set_cells(new_cells);
+    }
+
+    fn replace_cells(&mut self, cells: Vec<Self::Cell>) {
+        self.edited = true;
+        self.grid.replace_cells(cells);
+    }
+
+    fn changed(&self) -> Option<usize> { self.grid.changed() }
+}
+
+
+impl<C, G, Con> Engine for History<C, G, Con>
+    where C: Cell + Clone,
+          C::State: Clone,
+          G: Grid<Cell = C>,
+          Con: Consumer<Cell = C>,
+{
+    type Grid = G;
+
+    fn run_times(&mut self, times: u64) {
+        for _ in 0..times {
+            self.step();
+        }
+    }
+
+    fn run_until<S>(&mut self, mut condition: S) -> Stop
+        where S: StopCondition<G>,
+    {
+        loop {
+            let generation = self.generation();
+
+            if let Some(reason) = condition.check(self.engine.grid(), generation) {
+                return Stop {
+                    reason: reason,
+                    generation: generation,
+                };
+            }
+
+            self.step();
+        }
+    }
+}
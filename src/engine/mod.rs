@@ -2,5 +2,20 @@
 //! can be used to run evolutions.
 
 pub mod sequential;
+pub mod hashlife;
+pub mod stop;
+pub mod history;
+pub mod background;
+pub mod distributed;
+pub mod replay;
 
 pub use self::sequential::Sequential;
+pub use self::hashlife::HashLife;
+pub use self::history::History;
+pub use self::background::Background;
+pub use self::distributed::Distributed;
+pub use self::replay::Replay;
+pub use traits::Context;
+pub use traits::Stop;
+pub use traits::StopReason;
+pub use traits::StopCondition;
@@ -0,0 +1,246 @@
+//! Recording of external edits and engine
+//! which replays them.
+//!
+//! Evolution itself is deterministic (given seed of grid),
+//! so to reproduce session only edits made from outside
+//! should be kept. `Recorder` wraps grid and logs its initial
+//! cells and state together with every `set_cells` and
+//! `set_state` call, `Replay` applies logged edits at the
+//! same generations to grid of the same configuration.
+
+use std::time::Instant;
+
+use traits::Cell;
+use traits::Consumer;
+use traits::Grid;
+use traits::Engine;
+use engine::Context;
+use engine::Stop;
+use engine::StopCondition;
+
+
+/// Change made from outside after given generation
+/// (after its update and before the next one).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Edit<C, Es> {
+    /// Cells and state grid had when recording started.
+    /// Replaces all cells of grid on replay.
+    Initial {
+        /// All cells of grid.
+        cells: Vec<C>,
+        /// Evolution state.
+        state: Es,
+    },
+    /// Cells set with `Grid::set_cells`.
+    Cells {
+        /// Count of updates made by grid before edit.
+        generation: u64,
+        /// Cells which have been set.
+        cells: Vec<C>,
+    },
+    /// Evolution state set with `Grid::set_state`.
+    State {
+        /// Count of updates made by grid before edit.
+        generation: u64,
+        /// State which has been set.
+        state: Es,
+    },
+}
+
+
+impl<C, Es> Edit<C, Es> {
+    /// Returns count of updates made by grid before edit.
+    pub fn generation(&self) -> u64 {
+        match *self {
+            Edit::Initial { .. } => 0,
+            Edit::Cells { generation, .. } |
+            Edit::State { generation, .. } => generation,
+        }
+    }
+}
+
+
+/// Grid wrapper which logs every `set_cells` and `set_state`
+/// call together with count of updates made so far.
+pub struct Recorder<G: Grid> {
+    grid: G,
+    generation: u64,
+    log: Vec<Edit<G::Cell, <G::Cell as Cell>::State>>,
+}
+
+
+impl<G> Recorder<G>
+    where G: Grid,
+          G::Cell: Clone,
+          <G::Cell as Cell>::State: Clone,
+{
+    /// Wraps grid. Its current cells and state are
+    /// logged first, so replay starts from them.
+    /// Seed and other settings of grid are not logged.
+    pub fn new(grid: G) -> Self {
+        let log = vec![Edit::Initial {
+                           cells: grid.cells().to_vec(),
+                           state: grid.state().clone(),
+                       }];
+
+        Recorder {
+            grid: grid,
+            generation: 0,
+            log: log,
+        }
+    }
+
+    /// Returns count of updates made.
+    pub fn generation(&self) -> u64 { self.generation }
+
+    /// Returns edits logged so far.
+    pub fn log(&self) -> &[Edit<G::Cell, <G::Cell as Cell>::State>] { &self.log }
+
+    /// Getter for wrapped grid.
+    pub fn grid(&self) -> &G { &self.grid }
+
+    /// Returns wrapped grid and log.
+    pub fn into_parts(self) -> (G, Vec<Edit<G::Cell, <G::Cell as Cell>::State>>) {
+        (self.grid, self.log)
+    }
+}
+
+
+impl<G> Grid for Recorder<G>
+    where G: Grid,
+          G::Cell: Clone,
+          <G::Cell as Cell>::State: Clone,
+{
+    type Cell = G::Cell;
+    type Coord = G::Coord;
+
+    fn update(&mut self) {
+        self.grid.update();
+        self.generation += 1;
+    }
+
+    fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { self.grid.state() }
+
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) {
+        self.log.push(Edit::State {
+            generation: self.generation,
+            state: state.clone(),
+        });
+        self.grid.set_state(state);
+    }
+
+    fn cells(&self) -> &[Self::Cell] { self.grid.cells() }
+
+    fn size(&self) -> Self::Coord { self.grid.size() }
+
+    fn set_cells(&mut self, new_cells: Vec<Self::Cell>) {
+        self.log.push(Edit::Cells {
+            generation: self.generation,
+            cells: new_cells.clone(),
+        });
+        self.grid.set_cells(new_cells);
+    }
+
+    fn changed(&self) -> Option<usize> { self.grid.changed() }
+}
+
+
+/// Engine which replays evolution recorded with `Recorder`.
+/// Grid should be configured as recorded grid was (same
+/// dimensions, seed, update mode), initial cells and state
+/// are taken from log. On every generation consumer sees
+/// grid as recording consumer saw it, logged edits are
+/// applied after that. Consumer should not edit grid itself.
+pub struct Replay<C: Cell, G: Grid<Cell = C>, Con: Consumer<Cell = C>> {
+    grid: G,
+    /// Edits which have not been applied, the next one is the last.
+    log: Vec<Edit<C, C::State>>,
+    consumer: Con,
+    generation: u64,
+}
+
+
+impl<C: Cell, G: Grid<Cell = C>, Con: Consumer<Cell = C>> Replay<C, G, Con> {
+    /// Constructs engine. Edits made before the first
+    /// generation are applied at once.
+    pub fn new(grid: G, mut log: Vec<Edit<C, C::State>>, consumer: Con) -> Self {
+        log.reverse();
+
+        let mut replay = Replay {
+            grid: grid,
+            log: log,
+            consumer: consumer,
+            generation: 0,
+        };
+        replay.apply();
+
+        replay
+    }
+
+    /// Returns count of generations passed.
+    pub fn generation(&self) -> u64 { self.generation }
+
+    /// Getter for grid.
+    pub fn grid(&self) -> &G { &self.grid }
+
+    /// Returns `true` if all edits have been applied.
+    pub fn is_finished(&self) -> bool { self.log.is_empty() }
+
+    /// Applies edits logged for current generation.
+    fn apply(&mut self) {
+        while self.log.last().map_or(false, |edit| edit.generation() <= self.generation) {
+            match self.log.pop() {
+                Some(Edit::Initial { cells, state }) => {
+                    self.grid.replace_cells(cells);
+                    self.grid.set_state(state);
+                },
+                Some(Edit::Cells { cells, .. }) => self.grid.set_cells(cells),
+                Some(Edit::State { state, .. }) => self.grid.set_state(state),
+                None => break,
+            }
+        }
+    }
+
+    fn step(&mut self) {
+        let start = Instant::now();
+        self.grid.update();
+        let duration = start.elapsed();
+
+        self.generation += 1;
+
+        let context = Context {
+            generation: self.generation,
+            duration: duration,
+            changed: self.grid.changed(),
+        };
+        self.consumer.consume(&mut self.grid, &context);
+
+        self.apply();
+    }
+}
+
+
+impl<C: Cell, G: Grid<Cell = C>, Con: Consumer<Cell = C>> Engine for Replay<C, G, Con> {
+    type Grid = G;
+
+    fn run_times(&mut self, times: u64) {
+        for _ in 0..times {
+            self.step();
+        }
+    }
+
+    fn run_until<S>(&mut self, mut condition: S) -> Stop
+        where S: StopCondition<G>,
+    {
+        loop {
+            if let Some(reason) = condition.check(&self.grid, self.generation) {
+                return Stop {
+                    reason: reason,
+                    generation: self.generation,
+                };
+            }
+
+            self.step();
+        }
+    }
+}
@@ -2,10 +2,15 @@
 //! sequentially. Useful for test purposes
 //! and with grids which implemented interior parallelism.
 
+use std::time::Instant;
+
 use traits::Cell;
 use traits::Consumer;
 use traits::Grid;
 use traits::Engine;
+use engine::Context;
+use engine::Stop;
+use engine::StopCondition;
 
 
 /// Engine generic over Cell and Consumer running
@@ -13,6 +18,7 @@ use traits::Engine;
 pub struct Sequential<C: Cell, G: Grid<Cell = C>, Con: Consumer<Cell = C>> {
     grid: G,
     consumer: Con,
+    generation: u64,
 }
 
 impl<C: Cell, G: Grid<Cell = C>, Con: Consumer<Cell = C>> Sequential<C, G, Con> {
@@ -21,16 +27,71 @@ impl<C: Cell, G: Grid<Cell = C>, Con: Consumer<Cell = C>> Sequential<C, G, Con>
         Sequential {
             grid: grid,
             consumer: consumer,
+            generation: 0,
         }
     }
+
+    /// Returns count of generations passed.
+    pub fn generation(&self) -> u64 { self.generation }
+
+    /// Sets count of generations passed. Useful when
+    /// grid has been restored to some earlier state.
+    pub fn set_generation(&mut self, generation: u64) { self.generation = generation; }
+
+    /// Getter for grid.
+    pub fn grid(&self) -> &G { &self.grid }
+
+    /// Mutable getter for grid. Changes made through it
+    /// are not reported to consumer.
+    pub fn grid_mut(&mut self) -> &mut G { &mut self.grid }
+
+    /// Mutable getter for consumer.
+    pub fn consumer_mut(&mut self) -> &mut Con { &mut self.consumer }
+
+    /// Consumes engine and returns grid.
+    pub fn into_grid(self) -> G { self.grid }
+
+    /// Consumes engine and returns grid and consumer.
+    pub fn into_parts(self) -> (G, Con) { (self.grid, self.consumer) }
+
+    fn step(&mut self) {
+        let start = Instant::now();
+        self.grid.update();
+        let duration = start.elapsed();
+
+        self.generation += 1;
+
+        let context = Context {
+            generation: self.generation,
+            duration: duration,
+            changed: self.grid.changed(),
+        };
+        self.consumer.consume(&mut self.grid, &context);
+    }
 }
 
 
 impl<C: Cell, G: Grid<Cell = C>, Con: Consumer<Cell = C>> Engine for Sequential<C, G, Con> {
+    type Grid = G;
+
     fn run_times(&mut self, times: u64) {
         for _ in 0..times {
-            self.grid.update();
-            self.consumer.consume(&mut self.grid);
+            self.step();
+        }
+    }
+
+    fn run_until<S>(&mut self, mut condition: S) -> Stop
+        where S: StopCondition<G>,
+    {
+        loop {
+            if let Some(reason) = condition.check(&self.grid, self.generation) {
+                return Stop {
+                    reason: reason,
+                    generation: self.generation,
+                };
+            }
+
+            self.step();
         }
     }
 }
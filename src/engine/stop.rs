@@ -0,0 +1,133 @@
+//! Conditions which stop `Engine::run_until`.
+
+use std::collections::VecDeque;
+
+use traits::Grid;
+use traits::StopReason;
+use traits::StopCondition;
+
+
+/// Stops when predicate over grid becomes true.
+pub struct Predicate<F>(F);
+
+impl<F> Predicate<F> {
+    /// Just constructor.
+    pub fn new(predicate: F) -> Self { Predicate(predicate) }
+}
+
+impl<G, F> StopCondition<G> for Predicate<F>
+    where G: Grid,
+          F: FnMut(&G) -> bool,
+{
+    fn check(&mut self, grid: &G, _: u64) -> Option<StopReason> {
+        if (self.0)(grid) {
+            Some(StopReason::Predicate)
+        } else {
+            None
+        }
+    }
+}
+
+
+/// Stops when cells have not changed in last generation.
+pub struct FixedPoint<C> {
+    last: Option<Vec<C>>,
+}
+
+impl<C> FixedPoint<C> {
+    /// Just constructor.
+    pub fn new() -> Self { FixedPoint { last: None } }
+}
+
+impl<C, G> StopCondition<G> for FixedPoint<C>
+    where G: Grid<Cell = C>,
+          C: Clone + PartialEq,
+{
+    fn check(&mut self, grid: &G, _: u64) -> Option<StopReason> {
+
+        if let Some(ref last) = self.last {
+            if &last[..] == grid.cells() {
+                return Some(StopReason::FixedPoint);
+            }
+        }
+
+        self.last = Some(grid.cells().to_vec());
+
+        None
+    }
+}
+
+
+/// Stops when cells repeat state they had at most
+/// `max_period` generations ago. Keeps last `max_period`
+/// states of grid. Fixed point is a cycle with period 1.
+pub struct Cycle<C> {
+    history: VecDeque<Vec<C>>,
+    max_period: usize,
+}
+
+impl<C> Cycle<C> {
+    /// Constructs condition which detects cycles
+    /// not longer than `max_period`.
+    pub fn new(max_period: usize) -> Self {
+        Cycle {
+            history: VecDeque::with_capacity(max_period),
+            max_period: max_period,
+        }
+    }
+}
+
+impl<C, G> StopCondition<G> for Cycle<C>
+    where G: Grid<Cell = C>,
+          C: Clone + PartialEq,
+{
+    fn check(&mut self, grid: &G, _: u64) -> Option<StopReason> {
+
+        let cells = grid.cells();
+
+        let period = self.history.iter().position(|past| &past[..] == cells);
+        if let Some(period) = period {
+            return Some(StopReason::Cycle(period as u64 + 1));
+        }
+
+        if self.max_period > 0 {
+            if self.history.len() == self.max_period {
+                self.history.pop_back();
+            }
+            self.history.push_front(cells.to_vec());
+        }
+
+        None
+    }
+}
+
+
+/// Stops when given generation has been reached.
+pub struct MaxGenerations(pub u64);
+
+impl<G: Grid> StopCondition<G> for MaxGenerations {
+    fn check(&mut self, _: &G, generation: u64) -> Option<StopReason> {
+        if generation >= self.0 {
+            Some(StopReason::MaxGenerations)
+        } else {
+            None
+        }
+    }
+}
+
+
+/// Pair of conditions stops when any of them is met.
+/// Both conditions see every generation. Reason of
+/// the first one wins if both are met.
+impl<G, A, B> StopCondition<G> for (A, B)
+    where G: Grid,
+          A: StopCondition<G>,
+          B: StopCondition<G>,
+{
+    fn check(&mut self, grid: &G, generation: u64) -> Option<StopReason> {
+        let first = self.0.check(grid, generation);
+        let second = self.1.check(grid, generation);
+
+        first.or(second)
+    }
+}
@@ -1,52 +1,35 @@
 //! Implementation of [HPP model](https://en.wikipedia.org/wiki/HPP_model).
-//! Assumes Von Nuemann's neighborhood.
+//! Assumes neighborhood made by `nhood`: Von Neumann's one and
+//! cells two steps away along axes. The latter tell whether
+//! neighbor is at wall, so whole generation is computed at once.
 
 #![cfg(test)]
 
 use test::Bencher;
+use bincode;
+use bincode::SizeLimit;
 
 use traits::Cell;
+use traits::Neighbors;
+use traits::Offset;
 use traits::Coord;
 use traits::Grid;
 use traits::Engine;
-use traits::EvolutionState;
 use traits::Consumer;
 use engine::Sequential;
-use grid::nhood::VonNeumannNhood;
+use engine::Context;
+use grid::nhood::MaskNhood;
 use grid::twodim::TwodimGrid;
 use grid::twodim::GridCoord;
+use grid::twodim::Checkpoint;
+use grid::twodim::CheckpointError;
+use grid::Boundary;
+use grid::EmptyState;
+use grid::Iter;
+use grid::Pipeline;
 use utils::find_cell;
 
 
-enum Stage {
-    Collision,
-    Transport,
-}
-
-
-impl Default for Stage {
-    fn default() -> Self { Stage::Collision }
-}
-
-
-struct HPPState {
-    stage: Stage,
-}
-
-impl HPPState {
-    fn new() -> Self { HPPState { stage: Stage::Collision } }
-}
-
-impl EvolutionState for HPPState {
-    fn update(&mut self) {
-        self.stage = match self.stage {
-            Stage::Collision => Stage::Transport,
-            Stage::Transport => Stage::Collision,
-        };
-    }
-}
-
-
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Direction {
     Down,
@@ -57,6 +40,18 @@ enum Direction {
 
 
 impl Direction {
+    /// Returns direction of offset and distance along it.
+    fn from_offset(offset: &Offset) -> (Self, i32) {
+
+        match (offset.0, offset.1) {
+            (0, dy) if dy < 0 => (Direction::Up, -dy),
+            (dx, 0) if dx < 0 => (Direction::Left, -dx),
+            (dx, 0) if dx > 0 => (Direction::Right, dx),
+            (0, dy) if dy > 0 => (Direction::Down, dy),
+            _ => panic!("HPP supports only neighbors along axes"),
+        }
+    }
+
     fn opposite(&self) -> Self {
 
         match *self {
@@ -78,7 +73,11 @@ impl Direction {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+const DIRECTIONS: [Direction; 4] =
+    [Direction::Down, Direction::Right, Direction::Left, Direction::Up];
+
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 struct Particles([bool; 4]);
 
 impl Particles {
@@ -94,7 +93,7 @@ impl Particles {
 }
 
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 struct HPP {
     particles: Particles,
     coord: (i32, i32),
@@ -103,16 +102,22 @@ struct HPP {
 
 impl Cell for HPP {
     type Coord = (i32, i32);
-    type State = HPPState;
+    type State = EmptyState;
 
-    fn update<'a, I>(&'a mut self, old: &'a Self, neighbors: I, state: &Self::State)
-        where I: Iterator<Item = Option<&'a Self>>,
+    /// Collision goes first, then particles are transported.
+    /// Particles coming to cell are collided in neighbors, so
+    /// walls behind neighbors are checked too.
+    fn update<'a, I>(&'a mut self, old: &'a Self, neighbors: I, _: &Self::State)
+        where I: Neighbors<'a, Self>,
     {
+        let around = Around::new(neighbors);
+        let collided = old.particles.collision(|direction| around.wall(direction));
 
-        match state.stage {
-            Stage::Collision => self.collision(old, neighbors),
-            Stage::Transport => self.transport(old, neighbors),
-        };
+        // Only wall behind neighbor can turn its particles to cell.
+        self.particles = collided.transport(&around, |neighbor, direction| {
+            let behind = !around.far[*direction as usize];
+            neighbor.particles.collision(|wall| behind && wall == direction)
+        });
     }
 
     fn with_coord<C: Coord>(coord: C) -> Self {
@@ -125,79 +130,111 @@ impl Cell for HPP {
 }
 
 
-impl HPP {
-    fn collision<'a, I>(&'a mut self, old: &'a Self, neighbors: I)
-        where I: Iterator<Item = Option<&'a Self>>,
+/// Neighbors of cell by direction.
+struct Around<'a> {
+    near: [Option<&'a HPP>; 4],
+    /// Whether cells two steps away exist.
+    far: [bool; 4],
+}
+
+impl<'a> Around<'a> {
+    fn new<I: Neighbors<'a, HPP>>(neighbors: I) -> Self {
+        let mut around = Around {
+            near: [None; 4],
+            far: [false; 4],
+        };
+
+        for (neighbor, offset) in neighbors.with_offsets() {
+            match Direction::from_offset(offset) {
+                (direction, 1) => around.near[direction as usize] = neighbor,
+                (direction, _) => around.far[direction as usize] = neighbor.is_some(),
+            }
+        }
+
+        around
+    }
+
+    fn wall(&self, direction: &Direction) -> bool { self.near[*direction as usize].is_none() }
+}
+
+
+/// Von Neumann's neighborhood with cells two steps away along axes.
+fn nhood() -> MaskNhood<GridCoord> {
+    let offsets = vec![(0, -2), (0, -1), (-2, 0), (-1, 0), (1, 0), (2, 0), (0, 1), (0, 2)];
+    MaskNhood::from_offsets(offsets).unwrap()
+}
+
+
+/// Collision goes first, then particles are transported.
+fn pipeline() -> Pipeline<HPP> { Pipeline::new().then(collision).then(transport) }
+
+fn collision<'a>(cell: &'a mut HPP, old: &'a HPP, neighbors: Iter<'a, HPP>, _: &EmptyState) {
+    let around = Around::new(neighbors);
+    cell.particles = old.particles.collision(|direction| around.wall(direction));
+}
+
+fn transport<'a>(cell: &'a mut HPP, old: &'a HPP, neighbors: Iter<'a, HPP>, _: &EmptyState) {
+    let around = Around::new(neighbors);
+    cell.particles = old.particles.transport(&around, |neighbor, _| neighbor.particles);
+}
+
+
+impl Particles {
+    /// Collides particles of cell with walls in given directions.
+    fn collision<F>(&self, wall: F) -> Particles
+        where F: Fn(&Direction) -> bool,
     {
         let mut new = Particles::default();
 
         let has_head_on = |d: &Direction, op_d: &Direction| {
-            old.particles.get(d) && old.particles.get(op_d) &&
-            !old.particles.get(&d.perpendicular()) &&
-            !old.particles.get(&op_d.perpendicular())
+            self.get(d) && self.get(op_d) && !self.get(&d.perpendicular()) &&
+            !self.get(&op_d.perpendicular())
         };
 
-        for (neighbor, direction) in neighbors.zip(self.directions().iter()) {
-
-            match neighbor {
+        for direction in &DIRECTIONS {
 
-                Some(_) => {
-                    let opposite = direction.opposite();
+            let opposite = direction.opposite();
 
-                    let head_on = has_head_on(direction, &opposite);
-                    if head_on {
-                        new.set(&direction.perpendicular(), true);
-                        new.set(&opposite.perpendicular(), true);
-                    } else {
-                        let exists = new.get(direction) || old.particles.get(direction);
-                        new.set(direction, exists);
-                    }
-                },
+            if has_head_on(direction, &opposite) {
+                new.set(&direction.perpendicular(), true);
+                new.set(&opposite.perpendicular(), true);
+            } else if !wall(direction) {
+                let exists = new.get(direction) || self.get(direction);
+                new.set(direction, exists);
+            } else if self.get(direction) {
                 // Rebound
-                None => {
-                    let opposite = direction.opposite();
-                    let head_on = has_head_on(direction, &opposite);
-                    if head_on {
-                        new.set(&direction.perpendicular(), true);
-                        new.set(&opposite.perpendicular(), true);
-                    } else if old.particles.get(direction) {
-                        new.set(&opposite, true);
-                    }
-                },
+                new.set(&opposite, true);
             }
         }
 
-        self.particles = new;
+        new
     }
 
-    fn transport<'a, I>(&mut self, old: &'a Self, neighbors: I)
-        where I: Iterator<Item = Option<&'a Self>>,
+    /// Moves particles of neighbors to cell. `particles_of`
+    /// gives particles of neighbor in given direction.
+    fn transport<F>(&self, around: &Around, particles_of: F) -> Particles
+        where F: Fn(&HPP, &Direction) -> Particles,
     {
-
         let mut new = Particles::default();
 
-        for (neighbor, direction) in neighbors.zip(self.directions().iter()) {
-            match neighbor {
+        for direction in &DIRECTIONS {
+
+            match around.near[*direction as usize] {
                 Some(neighbor) => {
                     let opposite = direction.opposite();
-                    if neighbor.particles.get(&opposite) {
+                    if particles_of(neighbor, direction).get(&opposite) {
                         new.set(&opposite, true);
                     }
                 },
                 None => {
-                    if old.particles.get(direction) {
+                    if self.get(direction) {
                         new.set(direction, true);
                     }
                 },
             }
         }
 
-        self.particles = new;
-    }
-
-    #[inline]
-    pub fn directions(&self) -> [Direction; 4] {
-        [Direction::Up, Direction::Left, Direction::Right, Direction::Down]
+        new
     }
 }
 
@@ -322,15 +359,13 @@ impl HPPRulesTestConsumer {
 impl Consumer for HPPRulesTestConsumer {
     type Cell = HPP;
 
-    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G) {
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, _: &Context) {
         assert_eq!(grid.cells().len(), 9);
 
         pretty_print(grid);
 
-        match grid.state().stage {
-            Stage::Collision => self.test_transport(grid),
-            Stage::Transport => self.test_collision(grid),
-        }
+        // Consumer sees grid after the whole generation.
+        self.test_transport(grid);
     }
 }
 
@@ -354,10 +389,9 @@ fn test_rules() {
         HPP { particles: right_particle, coord: (0, 2) },
     ];
 
-    let nhood = VonNeumannNhood::new();
-    let evolution_state = HPPState::new();
-    let mut grid: TwodimGrid<HPP, _, _> = TwodimGrid::new(3, 3, nhood, evolution_state, 1);
-    grid.set_cells(cells);
+    let mut grid: TwodimGrid<HPP, _, _> =
+        TwodimGrid::new(3, 3, nhood(), Boundary::Open, EmptyState, 1);
+    grid.set_cells(cells.clone());
 
     pretty_print(&grid);
 
@@ -367,9 +401,20 @@ fn test_rules() {
         .count();
     assert_eq!(right_particles_count, 2);
 
+    // Collision alone.
+    grid.set_pipeline(Pipeline::new().then(collision));
+    grid.update();
+    pretty_print(&grid);
+    HPPRulesTestConsumer::new().test_collision(&grid);
+
+    let mut grid: TwodimGrid<HPP, _, _> =
+        TwodimGrid::new(3, 3, nhood(), Boundary::Open, EmptyState, 1);
+    grid.set_pipeline(pipeline());
+    grid.set_cells(cells);
+
     let consumer = HPPRulesTestConsumer::new();
     let mut engine = Sequential::new(grid, consumer);
-    engine.run_times(2);
+    engine.run_times(1);
 }
 
 
@@ -393,58 +438,57 @@ impl HPPSpreadTestConsumer {
             .count() as i32
     }
 
-    fn test_collision<G: Grid<Cell = HPP>>(&mut self, grid: &G) {
-        let size = grid.size();
-
+    /// Particles rebound from top and bottom on collision.
+    fn turn(&mut self, rows: i32) {
         if self.cur_y == 0 {
             self.cur_direction = Direction::Down;
-        }
-
-        let particles_count = self.particles_count(grid.cells(), &self.cur_direction);
-        assert_eq!(particles_count, size.x());
-
-
-        if self.cur_direction == Direction::Down {
-            self.cur_y += 1;
-        } else {
-            self.cur_y -= 1;
+        } else if self.cur_y == rows - 1 {
+            self.cur_direction = Direction::Up;
         }
     }
 
-    fn test_transport<G: Grid<Cell = HPP>>(&mut self, grid: &G) {
+    fn test_row<G: Grid<Cell = HPP>>(&self, grid: &G) {
         let size = grid.size();
 
         let particles_count = self.particles_count(grid.cells(), &self.cur_direction);
         assert_eq!(particles_count, size.x());
 
-
         assert!(grid.cells()
             .iter()
             .filter(|c| c.coord().y() == self.cur_y)
             .all(|c| c.particles.get(&self.cur_direction)));
+    }
 
-        if self.cur_y == size.x() - 1 {
-            self.cur_direction = Direction::Up;
+    fn test_collision<G: Grid<Cell = HPP>>(&mut self, grid: &G) {
+        self.turn(grid.size().y());
+        self.test_row(grid);
+    }
+
+    fn test_transport<G: Grid<Cell = HPP>>(&mut self, grid: &G) {
+        if self.cur_direction == Direction::Down {
+            self.cur_y += 1;
+        } else {
+            self.cur_y -= 1;
         }
+
+        self.test_row(grid);
     }
 }
 
 impl Consumer for HPPSpreadTestConsumer {
     type Cell = HPP;
 
-    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G) {
-        match grid.state().stage {
-            Stage::Collision => self.test_transport(grid),
-            Stage::Transport => self.test_collision(grid),
-        }
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, _: &Context) {
+        // Consumer sees grid after the whole generation,
+        // so collision is checked by `test_spread` only.
+        self.turn(grid.size().y());
+        self.test_transport(grid);
     }
 }
 
 
-fn setup_spread_test (rows: u32, cols: u32, threads: u32)
-         -> Sequential<HPP,
-                TwodimGrid<HPP, VonNeumannNhood<GridCoord>, HPPState>,
-                HPPSpreadTestConsumer> {
+fn spread_grid(rows: u32, cols: u32, threads: u32)
+               -> TwodimGrid<HPP, MaskNhood<GridCoord>, EmptyState> {
 
     let down_particle = Particles([false, false, false, true]);
 
@@ -456,14 +500,20 @@ fn setup_spread_test (rows: u32, cols: u32, threads: u32)
         });
     }
 
-    let nhood = VonNeumannNhood::new();
-    let evolution_state = HPPState::new();
     let mut grid: TwodimGrid<HPP, _, _> =
-        TwodimGrid::new(rows, cols, nhood, evolution_state, threads);
+        TwodimGrid::new(rows, cols, nhood(), Boundary::Open, EmptyState, threads);
+    grid.set_pipeline(pipeline());
     grid.set_cells(cells);
+    grid
+}
+
+fn setup_spread_test (rows: u32, cols: u32, threads: u32)
+         -> Sequential<HPP,
+                TwodimGrid<HPP, MaskNhood<GridCoord>, EmptyState>,
+                HPPSpreadTestConsumer> {
 
     let consumer = HPPSpreadTestConsumer::new();
-    Sequential::new(grid, consumer)
+    Sequential::new(spread_grid(rows, cols, threads), consumer)
 }
 
 
@@ -478,12 +528,115 @@ fn test_spread() {
     // 5x5 grid with 5 particles. Particles should move
     // to lower border then rebound, then move to upper and so on.
 
+    // Phases one by one.
+    let mut grid = spread_grid(5, 5, 1);
+    let mut consumer = HPPSpreadTestConsumer::new();
+    for _ in 0..10 {
+        grid.set_pipeline(Pipeline::new().then(collision));
+        grid.update();
+        consumer.test_collision(&grid);
+
+        grid.set_pipeline(Pipeline::new().then(transport));
+        grid.update();
+        consumer.test_transport(&grid);
+    }
+
     let mut engine = setup_spread_test(5, 5, 1);
-    // 2 phases * 10 full cycles = 20 times.
-    engine.run_times(20);
+    // Generation is full cycle of both phases.
+    engine.run_times(10);
+    assert_eq!(engine.generation(), 10);
 }
 
 
+#[test]
+fn test_update_matches_pipeline() {
+
+    let cells = vec![
+        HPP { particles: Particles([false, true, true, false]), coord: (1, 0) },
+        HPP { particles: Particles([false, false, true, false]), coord: (0, 1) },
+        HPP { particles: Particles([false, true, false, false]), coord: (0, 2) },
+        HPP { particles: Particles([true, false, false, true]), coord: (3, 3) },
+        HPP { particles: Particles([false, true, false, false]), coord: (3, 4) },
+    ];
+
+    let mut phased = spread_grid(5, 5, 1);
+    phased.set_cells(cells.clone());
+    phased.count_changes();
+
+    let mut grid = spread_grid(5, 5, 2);
+    grid.set_pipeline(Pipeline::new());
+    grid.set_cells(cells);
+
+    for _ in 0..12 {
+        let before = phased.cells().to_vec();
+        phased.update();
+        grid.update();
+
+        assert_eq!(phased.cells(), grid.cells());
+
+        // Changes are counted over the whole generation.
+        let changed = phased.cells().iter().zip(before.iter()).filter(|&(a, b)| a != b).count();
+        assert_eq!(phased.changed(), Some(changed));
+    }
+}
+
+
+struct NoopTestConsumer;
+
+impl Consumer for NoopTestConsumer {
+    type Cell = HPP;
+
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, _: &mut G, _: &Context) {}
+}
+
+
+#[test]
+fn test_checkpoint() {
+
+    let mut engine = setup_spread_test(5, 5, 1);
+    engine.run_times(3);
+
+    let checkpoint = engine.grid().checkpoint(engine.generation());
+    let bytes = bincode::serde::serialize(&checkpoint, SizeLimit::Infinite).unwrap();
+
+    let checkpoint: Checkpoint<HPP, EmptyState> = bincode::serde::deserialize(&bytes).unwrap();
+    assert_eq!(checkpoint.generation, 3);
+
+    let generation = checkpoint.generation;
+    let mut grid = TwodimGrid::from_checkpoint(checkpoint, nhood(), 2).unwrap();
+    // Phases are functions, so they are given on restore.
+    grid.set_pipeline(pipeline());
+    let mut restored = Sequential::new(grid, NoopTestConsumer);
+    restored.set_generation(generation);
+
+    engine.run_times(7);
+    restored.run_times(7);
+
+    assert_eq!(restored.generation(), 10);
+    assert_eq!(engine.grid().cells(), restored.grid().cells());
+}
+
+
+
+#[test]
+fn test_malformed_checkpoint() {
+
+    let engine = setup_spread_test(5, 5, 1);
+    let checkpoint = engine.grid().checkpoint(engine.generation());
+
+    let mut short = checkpoint.clone();
+    short.cells.pop();
+    let restored = TwodimGrid::from_checkpoint(short, nhood(), 1);
+    assert_eq!(restored.err(), Some(CheckpointError::CellsCount));
+
+    let mut misplaced = checkpoint.clone();
+    misplaced.cells.swap(0, 7);
+    let restored = TwodimGrid::from_checkpoint(misplaced, nhood(), 1);
+    assert_eq!(restored.err(), Some(CheckpointError::Coord));
+
+    assert!(TwodimGrid::from_checkpoint(checkpoint, nhood(), 1).is_ok());
+}
+
 #[bench]
 fn bench_multithread(b: &mut Bencher) {
     let cpus = ::num_cpus::get();
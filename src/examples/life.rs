@@ -1,13 +1,45 @@
 #![cfg(test)]
+use std::io;
+use std::thread;
+use std::net::TcpListener;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::time::Duration;
+
 use traits::Cell;
+use traits::Neighbors;
 use traits::Coord;
 use traits::Engine;
 use traits::Consumer;
 use traits::Grid;
 use engine::Sequential;
+use engine::HashLife;
+use engine::Context;
+use engine::History;
+use engine::Background;
+use engine::Distributed;
+use engine::distributed;
+use engine::Replay;
+use engine::replay::Recorder;
+use engine::replay::Edit;
+use engine::background::Command;
+use engine::background::FRAMES;
+use engine::StopReason;
+use consumer::Every;
+use consumer::At;
+use engine::stop::Cycle;
+use engine::stop::FixedPoint;
+use engine::stop::MaxGenerations;
+use engine::stop::Predicate;
 use grid::twodim::TwodimGrid;
+use grid::twodim::GridCoord;
+use grid::sparse::SparseGrid;
+use grid::hashlife::BinaryCell;
+use grid::hashlife::HashLifeGrid;
+use grid::hashlife::LifeRule;
 use grid::nhood::MooreNhood;
 use grid::EmptyState;
+use grid::Boundary;
 use utils::find_cell;
 
 /// Implementation of Conway's Game of Life.
@@ -17,7 +49,7 @@ enum LifeState {
     Alive,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 struct Life {
     state: LifeState,
     coord: (i32, i32),
@@ -58,7 +90,7 @@ impl Cell for Life {
     type State = EmptyState;
 
     fn update<'a, I>(&'a mut self, old: &'a Self, neighbors: I, _: &Self::State)
-        where I: Iterator<Item = Option<&'a Self>>,
+        where I: Neighbors<'a, Self>,
     {
         let alive_count = self.alive_count(neighbors);
 
@@ -83,6 +115,19 @@ impl Cell for Life {
 }
 
 
+impl BinaryCell for Life {
+    fn is_alive(&self) -> bool { self.state == LifeState::Alive }
+
+    fn set_alive(&mut self, alive: bool) {
+        self.state = if alive {
+            LifeState::Alive
+        } else {
+            LifeState::Dead
+        };
+    }
+}
+
+
 fn pretty_print<G: Grid<Cell = Life>>(grid: &G) {
     let dims = grid.size();
 
@@ -102,21 +147,16 @@ fn pretty_print<G: Grid<Cell = Life>>(grid: &G) {
     println!("");
 }
 
-struct SpinnerTestConsumer {
-    vertical: bool,
-}
-
-
-impl SpinnerTestConsumer {
-    pub fn new() -> Self { SpinnerTestConsumer { vertical: true } }
-}
+struct SpinnerTestConsumer;
 
 
 impl Consumer for SpinnerTestConsumer {
     type Cell = Life;
 
-    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G) {
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, context: &Context) {
         assert_eq!(grid.cells().len(), 9);
+        // Two cells die and two are born.
+        assert_eq!(context.changed, Some(4));
 
         pretty_print(grid);
 
@@ -133,15 +173,15 @@ impl Consumer for SpinnerTestConsumer {
         };
         assert_eq!(alive_cells().count(), 3);
 
-        self.vertical = !self.vertical;
+        let vertical = context.generation % 2 == 0;
 
         // if spinner is in vertical state
         if alive_cells().all(|c| c.coord.x() == 1) {
-            assert!(self.vertical);
+            assert!(vertical);
         }
         // if spinner is in horizontal state
         if alive_cells().all(|c| c.coord.y() == 1) {
-            assert!(!self.vertical);
+            assert!(!vertical);
         }
     }
 }
@@ -150,7 +190,8 @@ impl Consumer for SpinnerTestConsumer {
 fn test_game_of_life() {
 
     let nhood = MooreNhood::new();
-    let mut grid: TwodimGrid<Life, _, _> = TwodimGrid::new(3, 3, nhood, EmptyState, 1);
+    let mut grid: TwodimGrid<Life, _, _> =
+        TwodimGrid::new(3, 3, nhood, Boundary::Open, EmptyState, 1);
 
     // Should be in default state
     let default_state = LifeState::Dead;
@@ -177,10 +218,850 @@ fn test_game_of_life() {
                      }];
 
     grid.set_cells(cells);
+    grid.count_changes();
 
     pretty_print(&grid);
 
-    let consumer = SpinnerTestConsumer::new();
-    let mut engine = Sequential::new(grid, consumer);
+    let mut engine = Sequential::new(grid, SpinnerTestConsumer);
     engine.run_times(2);
 }
+
+
+#[test]
+fn test_glider_on_torus() {
+
+    let nhood = MooreNhood::new();
+    let mut grid: TwodimGrid<Life, _, _> =
+        TwodimGrid::new(6, 6, nhood, Boundary::Torus, EmptyState, 1);
+
+    // Glider
+    // D | A | D
+    // D | D | A
+    // A | A | A
+    let glider = vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+    let cells = glider.iter()
+        .map(|&(x, y)| {
+            Life {
+                state: LifeState::Alive,
+                coord: (x, y),
+            }
+        })
+        .collect();
+
+    grid.set_cells(cells);
+
+    // Glider moves by one cell diagonally every 4 generations,
+    // so it should cross whole grid and come back.
+    for _ in 0..24 {
+        grid.update();
+        let alive_count = grid.cells()
+            .iter()
+            .filter(|c| c.state == LifeState::Alive)
+            .count();
+        assert_eq!(alive_count, 5);
+    }
+
+    for &(x, y) in &glider {
+        assert_eq!(find_cell(grid.cells(), x, y).state, LifeState::Alive);
+    }
+}
+
+
+#[test]
+fn test_glider_on_sparse_grid() {
+
+    let nhood = MooreNhood::new();
+    let mut grid: SparseGrid<Life, _, _> = SparseGrid::new(nhood, EmptyState, 2);
+
+    let glider = vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+    let cells = glider.iter()
+        .map(|&(x, y)| {
+            Life {
+                state: LifeState::Alive,
+                coord: (x, y),
+            }
+        })
+        .collect();
+
+    grid.set_cells(cells);
+
+    // Glider flies away from the origin, far beyond the first tile.
+    for _ in 0..200 {
+        grid.update();
+        let alive_count = grid.cells()
+            .iter()
+            .filter(|c| c.state == LifeState::Alive)
+            .count();
+        assert_eq!(alive_count, 5);
+    }
+
+    for &(x, y) in &glider {
+        let cell = grid.get(x + 50, y + 50).expect("cell should be allocated");
+        assert_eq!(cell.state, LifeState::Alive);
+    }
+
+    // Tiles left behind should be freed.
+    assert!(grid.tiles_count() <= 4);
+    assert!(grid.get(0, 0).is_none());
+}
+
+
+#[test]
+fn test_sparse_grid_frees_dead_tiles() {
+
+    let nhood = MooreNhood::new();
+    let mut grid: SparseGrid<Life, _, _> = SparseGrid::new(nhood, EmptyState, 1);
+
+    grid.set_cells(vec![Life {
+                            state: LifeState::Alive,
+                            coord: (-100, 100),
+                        }]);
+    assert_eq!(grid.tiles_count(), 1);
+
+    // Lonely cell dies, so nothing should be left after next reshape.
+    grid.update();
+    grid.update();
+    assert_eq!(grid.tiles_count(), 0);
+    assert!(grid.cells().is_empty());
+}
+
+
+fn alive_coords<G: Grid<Cell = Life>>(grid: &G) -> Vec<(i32, i32)> {
+    let mut alive: Vec<_> = grid.cells()
+        .iter()
+        .filter(|c| c.state == LifeState::Alive)
+        .map(|c| c.coord)
+        .collect();
+    alive.sort();
+
+    alive
+}
+
+
+#[test]
+fn test_hashlife_matches_sparse_grid() {
+
+    // R-pentomino
+    // D | A | A
+    // A | A | D
+    // D | A | D
+    let cells: Vec<_> = vec![(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)]
+        .into_iter()
+        .map(|(x, y)| {
+            Life {
+                state: LifeState::Alive,
+                coord: (x, y),
+            }
+        })
+        .collect();
+
+    let nhood = MooreNhood::new();
+    let mut sparse: SparseGrid<Life, _, _> = SparseGrid::new(nhood, EmptyState, 2);
+    sparse.set_cells(cells.clone());
+
+    let mut hashlife = HashLifeGrid::new(LifeRule::conway(), EmptyState);
+    hashlife.set_cells(cells);
+
+    for _ in 0..10 {
+        sparse.update();
+        hashlife.update();
+    }
+    assert_eq!(alive_coords(&sparse), alive_coords(&hashlife));
+
+    for _ in 10..300 {
+        sparse.update();
+    }
+    hashlife.advance(290);
+
+    assert_eq!(hashlife.generation(), 300);
+    assert_eq!(alive_coords(&sparse), alive_coords(&hashlife));
+}
+
+
+#[test]
+fn test_hashlife_glider_far_away() {
+
+    let nhood = MooreNhood::new();
+    let mut grid: TwodimGrid<Life, _, _> =
+        TwodimGrid::new(8, 8, nhood, Boundary::Open, EmptyState, 1);
+
+    let glider = vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+    let cells = glider.iter()
+        .map(|&(x, y)| {
+            Life {
+                state: LifeState::Alive,
+                coord: (x, y),
+            }
+        })
+        .collect();
+    grid.set_cells(cells);
+
+    let mut hashlife = HashLifeGrid::from_grid(&grid, LifeRule::conway(), EmptyState);
+    assert_eq!(hashlife.population(), 5);
+
+    // Glider moves by one cell every 4 generations.
+    hashlife.step_pow2(30);
+    assert_eq!(hashlife.generation(), 1 << 30);
+    assert_eq!(hashlife.population(), 5);
+
+    let shift = 1 << 28;
+    for &(x, y) in &glider {
+        assert!(hashlife.is_alive(x + shift, y + shift));
+    }
+
+    // Glider is back in bounds of exported grid after
+    // moving it back, so export should see it at place.
+    let mut back = HashLifeGrid::new(LifeRule::conway(), EmptyState);
+    let moved: Vec<_> = hashlife.cells()
+        .iter()
+        .map(|c| {
+            Life {
+                state: LifeState::Alive,
+                coord: (c.coord.x() - shift + 2, c.coord.y() - shift + 2),
+            }
+        })
+        .collect();
+    back.set_cells(moved);
+    back.export(&mut grid);
+
+    let mut expected: Vec<_> = glider.iter().map(|&(x, y)| (x + 2, y + 2)).collect();
+    expected.sort();
+    assert_eq!(alive_coords(&grid), expected);
+}
+
+
+#[test]
+fn test_hashlife_huge_steps() {
+
+    let blinker = life_grid(5, 5, Boundary::Open, &[(1, 2), (2, 2), (3, 2)]);
+
+    let mut hashlife = HashLifeGrid::from_grid(&blinker, LifeRule::conway(), EmptyState);
+    hashlife.set_cache_limit(1000);
+
+    // Steps longer than quadtree allows are split.
+    hashlife.step_pow2(63);
+    assert_eq!(hashlife.generation(), 1 << 63);
+    assert_eq!(alive_coords(&hashlife), vec![(1, 2), (2, 2), (3, 2)]);
+
+    hashlife.advance((1 << 63) - 1);
+    assert_eq!(hashlife.generation(), u64::max_value());
+    assert_eq!(alive_coords(&hashlife), vec![(2, 1), (2, 2), (2, 3)]);
+
+    hashlife.clear_cache();
+    hashlife.advance(1);
+    assert_eq!(alive_coords(&hashlife), vec![(1, 2), (2, 2), (3, 2)]);
+}
+
+
+struct PopulationTestConsumer;
+
+impl Consumer for PopulationTestConsumer {
+    type Cell = Life;
+
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, _: &Context) {
+        assert_eq!(grid.cells().len(), 5);
+    }
+}
+
+
+/// Records generations it has been called on.
+struct GenerationsTestConsumer {
+    seen: Rc<RefCell<Vec<u64>>>,
+}
+
+impl Consumer for GenerationsTestConsumer {
+    type Cell = Life;
+
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, context: &Context) {
+        assert_eq!(grid.cells().len(), 5);
+        self.seen.borrow_mut().push(context.generation);
+    }
+}
+
+
+#[test]
+fn test_hashlife_engine() {
+
+    let glider = vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+    let cells = glider.iter()
+        .map(|&(x, y)| {
+            Life {
+                state: LifeState::Alive,
+                coord: (x, y),
+            }
+        })
+        .collect();
+
+    let mut grid = HashLifeGrid::new(LifeRule::conway(), EmptyState);
+    grid.set_cells(cells);
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let mut engine = HashLife::new(grid, GenerationsTestConsumer { seen: seen.clone() });
+
+    // Consumer is called once per batch and
+    // once per generation otherwise.
+    engine.run_batched(1000000);
+    engine.run_times(3);
+    assert_eq!(*seen.borrow(), vec![1000000, 1000001, 1000002, 1000003]);
+}
+
+
+struct NoopTestConsumer;
+
+impl Consumer for NoopTestConsumer {
+    type Cell = Life;
+
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, _: &mut G, _: &Context) {}
+}
+
+
+type LifeGrid = TwodimGrid<Life, MooreNhood<GridCoord>, EmptyState>;
+
+fn life_grid(rows: u32, cols: u32, boundary: Boundary<Life>, alive: &[(i32, i32)]) -> LifeGrid {
+
+    let nhood = MooreNhood::new();
+    let mut grid: LifeGrid = TwodimGrid::new(rows, cols, nhood, boundary, EmptyState, 1);
+
+    let cells = alive.iter()
+        .map(|&(x, y)| {
+            Life {
+                state: LifeState::Alive,
+                coord: (x, y),
+            }
+        })
+        .collect();
+    grid.set_cells(cells);
+
+    grid
+}
+
+
+#[test]
+fn test_run_until_fixed_point() {
+
+    // Three cells of block grow into the whole block
+    // on the first generation and stay still after that.
+    let grid = life_grid(4, 4, Boundary::Open, &[(1, 1), (2, 1), (1, 2)]);
+    let mut engine = Sequential::new(grid, NoopTestConsumer);
+
+    let stop = engine.run_until(FixedPoint::new());
+    assert_eq!(stop.reason, StopReason::FixedPoint);
+    assert_eq!(stop.generation, 2);
+    assert_eq!(engine.generation(), 2);
+}
+
+
+#[test]
+fn test_run_until_cycle() {
+
+    let blinker = life_grid(5, 5, Boundary::Open, &[(2, 1), (2, 2), (2, 3)]);
+    let mut engine = Sequential::new(blinker, NoopTestConsumer);
+
+    let stop = engine.run_until(Cycle::new(4));
+    assert_eq!(stop.reason, StopReason::Cycle(2));
+    assert_eq!(stop.generation, 2);
+
+    // Glider crosses torus 6x6 in 24 generations.
+    let glider = life_grid(6, 6, Boundary::Torus, &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    let mut engine = Sequential::new(glider, NoopTestConsumer);
+
+    let stop = engine.run_until((Cycle::new(30), FixedPoint::new()));
+    assert_eq!(stop.reason, StopReason::Cycle(24));
+    assert_eq!(stop.generation, 24);
+}
+
+
+fn glider_moved(grid: &LifeGrid) -> bool {
+    find_cell(grid.cells(), 3, 3).state == LifeState::Alive
+}
+
+
+#[test]
+fn test_run_until_predicate() {
+
+    let glider = life_grid(6, 6, Boundary::Torus, &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    let mut engine = Sequential::new(glider, NoopTestConsumer);
+
+    // Glider moves by one cell in 4 generations.
+    let stop = engine.run_until((MaxGenerations(3), Predicate::new(glider_moved)));
+    assert_eq!(stop.reason, StopReason::MaxGenerations);
+    assert_eq!(stop.generation, 3);
+
+    let stop = engine.run_until((MaxGenerations(100), Predicate::new(glider_moved)));
+    assert_eq!(stop.reason, StopReason::Predicate);
+    assert_eq!(stop.generation, 4);
+}
+
+
+#[test]
+fn test_hashlife_run_until() {
+
+    let mut grid = HashLifeGrid::new(LifeRule::conway(), EmptyState);
+    grid.set_cells(vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+        .into_iter()
+        .map(|(x, y)| {
+            Life {
+                state: LifeState::Alive,
+                coord: (x, y),
+            }
+        })
+        .collect());
+
+    let mut engine = HashLife::new(grid, PopulationTestConsumer);
+    engine.run_times(10);
+
+    let stop = engine.run_until(MaxGenerations(100));
+    assert_eq!(stop.generation, 100);
+}
+
+
+/// Records populations it has seen.
+struct PopulationsTestConsumer {
+    seen: Rc<RefCell<Vec<usize>>>,
+}
+
+impl PopulationsTestConsumer {
+    fn new() -> (Self, Rc<RefCell<Vec<usize>>>) {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        (PopulationsTestConsumer { seen: seen.clone() }, seen)
+    }
+}
+
+impl Consumer for PopulationsTestConsumer {
+    type Cell = Life;
+
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, _: &Context) {
+        let population = grid.cells()
+            .iter()
+            .filter(|c| c.state == LifeState::Alive)
+            .count();
+
+        self.seen.borrow_mut().push(population);
+    }
+}
+
+
+#[test]
+fn test_consumers_fan_out() {
+
+    // R-pentomino changes its population almost every generation.
+    let grid = life_grid(10, 10, Boundary::Open, &[(5, 4), (6, 4), (4, 5), (5, 5), (5, 6)]);
+
+    let (all, all_seen) = PopulationsTestConsumer::new();
+    let (every, every_seen) = PopulationsTestConsumer::new();
+    let (at, at_seen) = PopulationsTestConsumer::new();
+
+    let consumers = (all, Every::new(3, every), At::new(vec![5, 1, 100], at));
+    let mut engine = Sequential::new(grid, consumers);
+    engine.run_times(10);
+
+    let all_seen = all_seen.borrow();
+    assert_eq!(all_seen.len(), 10);
+
+    let expected: Vec<_> = vec![3, 6, 9].into_iter().map(|g| all_seen[g - 1]).collect();
+    assert_eq!(*every_seen.borrow(), expected);
+
+    let expected: Vec<_> = vec![1, 5].into_iter().map(|g| all_seen[g - 1]).collect();
+    assert_eq!(*at_seen.borrow(), expected);
+}
+
+
+#[test]
+fn test_history_rewind() {
+
+    let pentomino = [(5, 4), (6, 4), (4, 5), (5, 5), (5, 6)];
+
+    let mut reference = life_grid(10, 10, Boundary::Open, &pentomino);
+    let mut expected = vec![reference.cells().to_vec()];
+    for _ in 0..30 {
+        reference.update();
+        expected.push(reference.cells().to_vec());
+    }
+
+    let grid = life_grid(10, 10, Boundary::Open, &pentomino);
+    let engine = Sequential::new(grid, NoopTestConsumer);
+    let mut history = History::new(engine, 4, 8);
+    history.run_times(30);
+
+    history.step_back(2);
+    assert_eq!(history.generation(), 28);
+    assert_eq!(history.grid().cells(), &expected[28][..]);
+
+    // Older than ring buffer, re-simulated from keyframe 0.
+    history.seek(5);
+    assert_eq!(history.generation(), 5);
+    assert_eq!(history.grid().cells(), &expected[5][..]);
+
+    history.seek(3);
+    assert_eq!(history.grid().cells(), &expected[3][..]);
+
+    history.seek(20);
+    assert_eq!(history.grid().cells(), &expected[20][..]);
+
+    // Ring buffer keeps generations 17-20 now,
+    // so it is re-simulated from keyframe 8.
+    history.step_back(5);
+    assert_eq!(history.generation(), 15);
+    assert_eq!(history.grid().cells(), &expected[15][..]);
+
+    history.step_back(100);
+    assert_eq!(history.generation(), 0);
+    assert_eq!(history.grid().cells(), &expected[0][..]);
+}
+
+
+
+#[test]
+fn test_history_keyframes_limit() {
+
+    let pentomino = [(5, 4), (6, 4), (4, 5), (5, 5), (5, 6)];
+
+    let mut reference = life_grid(10, 10, Boundary::Open, &pentomino);
+    let mut expected = vec![reference.cells().to_vec()];
+    for _ in 0..100 {
+        reference.update();
+        expected.push(reference.cells().to_vec());
+    }
+
+    let grid = life_grid(10, 10, Boundary::Open, &pentomino);
+    let engine = Sequential::new(grid, NoopTestConsumer);
+    let mut history = History::new(engine, 2, 1);
+    history.set_max_keyframes(8);
+
+    for _ in 0..100 {
+        history.run_times(1);
+        assert!(history.keyframes_count() <= 8);
+    }
+
+    // Thinned keyframes still cover whole evolution.
+    history.seek(37);
+    assert_eq!(history.grid().cells(), &expected[37][..]);
+    history.seek(1);
+    assert_eq!(history.grid().cells(), &expected[1][..]);
+}
+
+
+#[test]
+fn test_history_rewind_sparse_grid() {
+
+    let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+    let cells = || {
+        glider.iter()
+            .map(|&(x, y)| {
+                Life {
+                    state: LifeState::Alive,
+                    coord: (x, y),
+                }
+            })
+            .collect()
+    };
+
+    let mut reference: SparseGrid<Life, _, _> = SparseGrid::new(MooreNhood::new(), EmptyState, 1);
+    reference.set_cells(cells());
+    let mut expected = vec![alive_coords(&reference)];
+    for _ in 0..80 {
+        reference.update();
+        expected.push(alive_coords(&reference));
+    }
+
+    let mut grid: SparseGrid<Life, _, _> = SparseGrid::new(MooreNhood::new(), EmptyState, 1);
+    grid.set_cells(cells());
+    let engine = Sequential::new(grid, NoopTestConsumer);
+    let mut history = History::new(engine, 2, 16);
+    history.run_times(80);
+
+    // Glider has left tiles of keyframe, they should not
+    // be kept after rewind.
+    history.seek(3);
+    assert_eq!(alive_coords(history.grid()), expected[3]);
+    history.seek(40);
+    assert_eq!(alive_coords(history.grid()), expected[40]);
+}
+
+#[test]
+fn test_history_rewind_edited() {
+
+    let pentomino = [(5, 4), (6, 4), (4, 5), (5, 5), (5, 6)];
+
+    let mut reference = life_grid(10, 10, Boundary::Open, &pentomino);
+    let mut expected = vec![reference.cells().to_vec()];
+    for generation in 1..31 {
+        reference.update();
+        if generation == 22 {
+            let dead = reference.cells().iter().map(|c| Life::with_coord(c.coord)).collect();
+            reference.set_cells(dead);
+        }
+        expected.push(reference.cells().to_vec());
+    }
+
+    let grid = life_grid(10, 10, Boundary::Open, &pentomino);
+    let consumer = ClearingTestConsumer {
+        clear_at: 22,
+        seen: Vec::new(),
+    };
+    let engine = Sequential::new(grid, consumer);
+    let mut history = History::new(engine, 2, 4);
+    history.run_times(30);
+
+    // Consumer has cleared grid on generation 22, so
+    // it is re-simulated from there, not from keyframe 20.
+    history.seek(23);
+    assert_eq!(history.grid().cells(), &expected[23][..]);
+    history.seek(30);
+    assert_eq!(history.grid().cells(), &expected[30][..]);
+
+    // Keyframes before edit are dropped.
+    history.seek(10);
+    assert_eq!(history.generation(), 22);
+    assert_eq!(history.grid().cells(), &expected[22][..]);
+}
+
+/// Records generations it has seen and clears
+/// grid on given generation.
+struct ClearingTestConsumer {
+    clear_at: u64,
+    seen: Vec<(u64, usize)>,
+}
+
+impl Consumer for ClearingTestConsumer {
+    type Cell = Life;
+
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, context: &Context) {
+        let alive: Vec<_> = grid.cells()
+            .iter()
+            .filter(|c| c.state == LifeState::Alive)
+            .cloned()
+            .collect();
+
+        self.seen.push((context.generation, alive.len()));
+
+        if context.generation == self.clear_at {
+            let dead = alive.into_iter()
+                .map(|c| Life::with_coord(c.coord))
+                .collect();
+            grid.set_cells(dead);
+        }
+    }
+}
+
+
+#[test]
+fn test_background_engine() {
+
+    let blinker = life_grid(5, 5, Boundary::Open, &[(2, 1), (2, 2), (2, 3)]);
+    let mut background = Background::spawn(blinker);
+
+    let mut consumer = ClearingTestConsumer {
+        clear_at: 3,
+        seen: Vec::new(),
+    };
+
+    background.send(Command::Step);
+    background.send(Command::Step);
+    assert!(background.wait(&mut consumer));
+    assert!(background.wait(&mut consumer));
+    assert_eq!(consumer.seen, vec![(1, 3), (2, 3)]);
+
+    background.send(Command::Speed(Duration::from_millis(1)));
+    background.send(Command::Resume);
+
+    // Grid is cleared by consumer on generation 3, so
+    // worker publishes frame with empty grid after that.
+    while consumer.seen[consumer.seen.len() - 1].1 != 0 {
+        assert!(background.wait(&mut consumer));
+    }
+    background.send(Command::Pause);
+
+    assert_eq!(consumer.seen[2], (3, 3));
+
+    let grid = background.stop();
+    assert!(grid.cells().iter().all(|c| c.state == LifeState::Dead));
+}
+
+
+
+#[test]
+fn test_background_engine_bounded_frames() {
+
+    let blinker = life_grid(5, 5, Boundary::Open, &[(2, 1), (2, 2), (2, 3)]);
+    let mut background = Background::spawn(blinker);
+
+    let mut consumer = ClearingTestConsumer {
+        clear_at: 0,
+        seen: Vec::new(),
+    };
+
+    background.send(Command::Resume);
+    assert!(background.wait(&mut consumer));
+    background.send(Command::Pause);
+    assert_eq!(consumer.seen[0].0, 1);
+
+    // Worker waits for frames to be consumed, so only
+    // full queue and frame it was sending are left.
+    let count = background.poll(&mut consumer);
+    assert!(count <= FRAMES + 1);
+
+    background.stop();
+}
+
+/// Sets state of grid on every call, so it is sent to workers.
+struct ResettingTestConsumer;
+
+impl Consumer for ResettingTestConsumer {
+    type Cell = Life;
+
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, _: &Context) {
+        grid.set_state(EmptyState);
+    }
+}
+
+
+#[test]
+fn test_distributed_engine() {
+
+    let r_pentomino = [(6, 5), (7, 5), (5, 6), (6, 6), (6, 7)];
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+
+    let workers: Vec<_> = (0..3)
+        .map(|_| {
+            let address = address.clone();
+            thread::spawn(move || {
+                distributed::work::<Life, _, EmptyState, _>(&address[..], MooreNhood::new(), 1)
+            })
+        })
+        .collect();
+
+    let grid = life_grid(12, 12, Boundary::Torus, &r_pentomino);
+    let mut sequential = Sequential::new(grid, NoopTestConsumer);
+    sequential.run_times(30);
+
+    {
+        let grid = life_grid(12, 12, Boundary::Torus, &r_pentomino);
+        let mut engine = Distributed::new(grid, &listener, 3, ResettingTestConsumer).unwrap();
+        engine.run_times(15);
+        engine.run_batched(15);
+        assert_eq!(engine.generation(), 30);
+        assert_eq!(engine.grid().cells(), sequential.grid().cells());
+    }
+
+    for worker in workers {
+        assert!(worker.join().unwrap().is_ok());
+    }
+
+    let grid = life_grid(12, 12, Boundary::Torus, &r_pentomino);
+    let error = Distributed::new(grid, &listener, 0, NoopTestConsumer).err().unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+}
+
+
+/// Sets given cells at given generations
+/// and records populations it has seen.
+struct PaintingTestConsumer {
+    edits: Vec<(u64, Vec<(i32, i32)>)>,
+    populations: PopulationsTestConsumer,
+}
+
+impl Consumer for PaintingTestConsumer {
+    type Cell = Life;
+
+    fn consume<G: Grid<Cell = Self::Cell>>(&mut self, grid: &mut G, context: &Context) {
+        self.populations.consume(grid, context);
+
+        for &(generation, ref alive) in &self.edits {
+            if generation == context.generation {
+                let cells = alive.iter()
+                    .map(|&(x, y)| {
+                        Life {
+                            state: LifeState::Alive,
+                            coord: (x, y),
+                        }
+                    })
+                    .collect();
+                grid.set_cells(cells);
+            }
+        }
+    }
+}
+
+
+#[test]
+fn test_replay() {
+
+    let glider = vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+    let block = vec![(6, 6), (7, 6), (6, 7), (7, 7)];
+
+    let mut recorder = Recorder::new(life_grid(10, 10, Boundary::Torus, &[]));
+    recorder.set_cells(vec![Life {
+                                state: LifeState::Alive,
+                                coord: (5, 5),
+                            }]);
+    recorder.set_state(EmptyState);
+
+    let (populations, recorded_seen) = PopulationsTestConsumer::new();
+    let painter = PaintingTestConsumer {
+        edits: vec![(2, glider), (7, block)],
+        populations: populations,
+    };
+    let mut engine = Sequential::new(recorder, painter);
+    engine.run_times(15);
+
+    let recorder = engine.into_grid();
+    let generations: Vec<_> = recorder.log().iter().map(|edit| edit.generation()).collect();
+    assert_eq!(generations, vec![0, 0, 0, 2, 7]);
+
+    // Initial cells and state are logged first.
+    match (&recorder.log()[0], &recorder.log()[1], &recorder.log()[2]) {
+        (&Edit::Initial { ref cells, .. }, &Edit::Cells { .. }, &Edit::State { .. }) => {
+            assert_eq!(cells.len(), 100);
+        },
+        _ => panic!("unexpected edits"),
+    }
+
+    let (recorded, log) = recorder.into_parts();
+
+    // Initial cells of grid are replaced from log.
+    let grid = life_grid(10, 10, Boundary::Torus, &[(3, 3)]);
+    let (viewer, replayed_seen) = PopulationsTestConsumer::new();
+    let mut replay = Replay::new(grid, log, viewer);
+    replay.run_times(15);
+
+    assert!(replay.is_finished());
+    assert_eq!(replay.grid().cells(), recorded.cells());
+    assert_eq!(*replayed_seen.borrow(), *recorded_seen.borrow());
+}
+
+
+#[test]
+fn test_replay_sparse_grid() {
+
+    let block = [(0, 0), (0, 1), (1, 0), (1, 1)];
+    let alive = |coords: &[(i32, i32)]| -> Vec<Life> {
+        coords.iter()
+            .map(|&(x, y)| {
+                Life {
+                    state: LifeState::Alive,
+                    coord: (x, y),
+                }
+            })
+            .collect()
+    };
+
+    let mut grid: SparseGrid<Life, _, _> = SparseGrid::new(MooreNhood::new(), EmptyState, 1);
+    grid.set_cells(alive(&block));
+    let mut recorder = Recorder::new(grid);
+    for _ in 0..3 {
+        recorder.update();
+    }
+    let (recorded, log) = recorder.into_parts();
+
+    // Block in tile recorded grid doesn't have
+    // should be dropped by initial edit.
+    let mut grid: SparseGrid<Life, _, _> = SparseGrid::new(MooreNhood::new(), EmptyState, 1);
+    grid.set_cells(alive(&[(40, 40), (41, 40), (40, 41), (41, 41)]));
+    let mut replay = Replay::new(grid, log, NoopTestConsumer);
+    replay.run_times(3);
+
+    assert_eq!(alive_coords(replay.grid()), alive_coords(&recorded));
+    assert_eq!(alive_coords(&recorded), block.to_vec());
+}
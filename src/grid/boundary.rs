@@ -0,0 +1,209 @@
+//! Boundary conditions for grids with edges.
+
+
+/// Policy describing what lies beyond the edges of grid.
+/// Grids resolve neighbors once on construction, so
+/// chosen policy costs nothing during evolution.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Boundary<C> {
+    /// Neighbors outside of grid are `None`.
+    Open,
+    /// Opposite edges are glued together (periodic domain).
+    Torus,
+    /// Left and right edges are glued together as in `Torus`,
+    /// top and bottom ones are glued with a twist: crossing
+    /// them mirrors `x` coordinate.
+    Klein,
+    /// Edges are mirrors: neighbor which is `n` cells
+    /// beyond the edge is the `n`-th cell inside of grid
+    /// counting from the edge.
+    Reflect,
+    /// Every neighbor outside of grid is given "ghost"
+    /// cell (Dirichlet boundary condition).
+    Fixed(C),
+}
+
+
+impl<C> Default for Boundary<C> {
+    fn default() -> Self { Boundary::Open }
+}
+
+
+impl<C> Boundary<C> {
+    /// Maps given (possibly out-of-range) coord onto grid
+    /// with `cols` columns and `rows` rows. Returns `None`
+    /// if coord lies outside of grid and policy does not
+    /// bring it back (`Open` and `Fixed`).
+    pub fn resolve(&self, x: i32, y: i32, cols: i32, rows: i32) -> Option<(i32, i32)> {
+
+        if inside(x, cols) && inside(y, rows) {
+            return Some((x, y));
+        }
+
+        match *self {
+            Boundary::Open |
+            Boundary::Fixed(_) => None,
+            Boundary::Torus => Some((wrap(x, cols).0, wrap(y, rows).0)),
+            Boundary::Klein => {
+                let (y, turns) = wrap(y, rows);
+                let x = if turns % 2 != 0 { cols - 1 - x } else { x };
+
+                Some((wrap(x, cols).0, y))
+            },
+            Boundary::Reflect => Some((reflect(x, cols), reflect(y, rows))),
+        }
+    }
+
+    /// Maps given coord onto 3D grid with `cols` columns,
+    /// `rows` rows and `layers` layers. Policy is applied to
+    /// `x` and `y` as in `resolve`. Along `z` axis `Klein` is
+    /// the same as `Torus`.
+    pub fn resolve_3d(&self,
+                      x: i32,
+                      y: i32,
+                      z: i32,
+                      cols: i32,
+                      rows: i32,
+                      layers: i32)
+                      -> Option<(i32, i32, i32)> {
+
+        let z = if inside(z, layers) {
+            z
+        } else {
+            match *self {
+                Boundary::Open |
+                Boundary::Fixed(_) => return None,
+                Boundary::Torus |
+                Boundary::Klein => wrap(z, layers).0,
+                Boundary::Reflect => reflect(z, layers),
+            }
+        };
+
+        self.resolve(x, y, cols, rows).map(|(x, y)| (x, y, z))
+    }
+
+    /// Returns cell which stands for every neighbor outside
+    /// of grid. Only `Fixed` policy has one.
+    #[inline]
+    pub fn ghost(&self) -> Option<&C> {
+        match *self {
+            Boundary::Fixed(ref cell) => Some(cell),
+            _ => None,
+        }
+    }
+}
+
+
+#[inline]
+fn inside(value: i32, len: i32) -> bool { value >= 0 && value < len }
+
+/// Wraps value into `0..len` range. Returns wrapped value
+/// and number of times range was crossed (negative when
+/// crossed through lower edge).
+#[inline]
+fn wrap(value: i32, len: i32) -> (i32, i32) {
+    let turns = if value >= 0 {
+        value / len
+    } else {
+        (value + 1) / len - 1
+    };
+
+    (value - turns * len, turns)
+}
+
+/// Mirrors value into `0..len` range. Edge cells are
+/// mirrored too, so `-1` becomes `0` and `len` becomes `len - 1`.
+#[inline]
+fn reflect(value: i32, len: i32) -> i32 {
+    let (value, _) = wrap(value, 2 * len);
+
+    if value < len { value } else { 2 * len - 1 - value }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::Boundary;
+
+    #[test]
+    fn test_inside_is_untouched() {
+        let boundaries = [Boundary::Open,
+                          Boundary::Torus,
+                          Boundary::Klein,
+                          Boundary::Reflect,
+                          Boundary::Fixed(())];
+
+        for boundary in &boundaries {
+            assert_eq!(boundary.resolve(1, 2, 3, 4), Some((1, 2)));
+        }
+    }
+
+    #[test]
+    fn test_open() {
+        let boundary: Boundary<()> = Boundary::Open;
+
+        assert_eq!(boundary.resolve(-1, 0, 3, 3), None);
+        assert_eq!(boundary.resolve(0, 3, 3, 3), None);
+    }
+
+    #[test]
+    fn test_torus() {
+        let boundary: Boundary<()> = Boundary::Torus;
+
+        assert_eq!(boundary.resolve(-1, -1, 3, 4), Some((2, 3)));
+        assert_eq!(boundary.resolve(3, 4, 3, 4), Some((0, 0)));
+        assert_eq!(boundary.resolve(1, -5, 3, 4), Some((1, 3)));
+    }
+
+    #[test]
+    fn test_klein() {
+        let boundary: Boundary<()> = Boundary::Klein;
+
+        // Crossing left or right edge is the same as on torus.
+        assert_eq!(boundary.resolve(-1, 1, 3, 4), Some((2, 1)));
+        assert_eq!(boundary.resolve(3, 1, 3, 4), Some((0, 1)));
+        // Crossing top or bottom edge mirrors x.
+        assert_eq!(boundary.resolve(0, -1, 3, 4), Some((2, 3)));
+        assert_eq!(boundary.resolve(0, 4, 3, 4), Some((2, 0)));
+        assert_eq!(boundary.resolve(-1, 4, 3, 4), Some((0, 0)));
+        // Crossing twice brings orientation back.
+        assert_eq!(boundary.resolve(0, 8, 3, 4), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_reflect() {
+        let boundary: Boundary<()> = Boundary::Reflect;
+
+        assert_eq!(boundary.resolve(-1, 0, 3, 4), Some((0, 0)));
+        assert_eq!(boundary.resolve(-2, 0, 3, 4), Some((1, 0)));
+        assert_eq!(boundary.resolve(3, 4, 3, 4), Some((2, 3)));
+        assert_eq!(boundary.resolve(4, -1, 3, 4), Some((1, 0)));
+    }
+
+    #[test]
+    fn test_resolve_3d() {
+        let boundary: Boundary<()> = Boundary::Open;
+        assert_eq!(boundary.resolve_3d(0, 0, 3, 2, 2, 3), None);
+
+        let boundary: Boundary<()> = Boundary::Torus;
+        assert_eq!(boundary.resolve_3d(-1, 0, 3, 2, 2, 3), Some((1, 0, 0)));
+
+        let boundary: Boundary<()> = Boundary::Klein;
+        assert_eq!(boundary.resolve_3d(0, -1, -1, 2, 2, 3), Some((1, 1, 2)));
+
+        let boundary: Boundary<()> = Boundary::Reflect;
+        assert_eq!(boundary.resolve_3d(0, 0, -1, 2, 2, 3), Some((0, 0, 0)));
+    }
+
+    #[test]
+    fn test_fixed() {
+        let boundary = Boundary::Fixed(42);
+
+        assert_eq!(boundary.resolve(-1, 0, 3, 3), None);
+        assert_eq!(boundary.ghost(), Some(&42));
+
+        let boundary: Boundary<i32> = Boundary::Torus;
+        assert_eq!(boundary.ghost(), None);
+    }
+}
@@ -0,0 +1,409 @@
+//! Storage shared by grids which keep cells in
+//! one-dimensional `Vec` and know neighbors of every
+//! cell in advance.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+use scoped_threadpool::Pool;
+
+use traits::Cell;
+use traits::EvolutionState;
+use traits::Offset;
+use rng::Rng;
+
+use super::iter::Iter;
+use super::pipeline;
+use super::pipeline::Phase;
+
+
+/// Cells storage implemented with two buffers.
+/// They are swapped on every evolution step.
+/// Old buffer is used for read-only neighbors data.
+/// New buffer is writable and mutated through update process,
+/// which is split between threads of pool.
+/// Offsets and weights of neighbors are stored once for
+/// every distinct pattern, cells keep only index of pattern.
+/// Random streams of cells are derived from seed, steps
+/// count, phase and index of cell. Changed cells are counted
+/// only if counting has been enabled.
+pub struct DoubleBuffer<C> {
+    pub cells: Vec<C>,
+    pub old_cells: Vec<C>,
+    pub neighbors: Vec<Vec<Option<usize>>>,
+    pub patterns: Vec<usize>,
+    offsets: Vec<Vec<Offset>>,
+    weights: Vec<Vec<f64>>,
+    known: HashMap<Vec<Offset>, usize>,
+    pub seed: u64,
+    pub steps: u64,
+    pub probability: f64,
+    compare: Option<fn(&C, &C) -> bool>,
+    changed: usize,
+    pool: Pool,
+    parts: Vec<(usize, usize)>,
+}
+
+
+impl<C: Cell + Clone> DoubleBuffer<C> {
+    /// Constructs empty buffer for `len` cells
+    /// which will be updated in `threads` threads.
+    pub fn new(len: usize, threads: u32) -> Self {
+
+        let threads = cmp::max(threads, 1);
+
+        DoubleBuffer {
+            cells: Vec::with_capacity(len),
+            old_cells: Vec::with_capacity(len),
+            neighbors: Vec::with_capacity(len),
+            patterns: Vec::with_capacity(len),
+            offsets: Vec::new(),
+            weights: Vec::new(),
+            known: HashMap::new(),
+            seed: 0,
+            steps: 0,
+            probability: 1.0,
+            compare: None,
+            changed: 0,
+            pool: Pool::new(threads),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Adds cell with indices of its neighbors in `cells`,
+    /// their offsets relative to cell and weights.
+    pub fn push(&mut self,
+                cell: C,
+                neighbors: Vec<Option<usize>>,
+                offsets: Vec<Offset>,
+                weights: Vec<f64>) {
+        let pattern = self.pattern(offsets, weights);
+
+        self.patterns.push(pattern);
+        self.neighbors.push(neighbors);
+        self.old_cells.push(cell.clone());
+        self.cells.push(cell);
+    }
+
+    /// Returns index of given offsets pattern.
+    /// Unknown pattern is stored first. Weights depend
+    /// only on offsets, so they are stored with pattern.
+    pub fn pattern(&mut self, offsets: Vec<Offset>, weights: Vec<f64>) -> usize {
+
+        if let Some(&pattern) = self.known.get(&offsets) {
+            return pattern;
+        }
+
+        let pattern = self.offsets.len();
+        self.offsets.push(offsets.clone());
+        self.weights.push(weights);
+        self.known.insert(offsets, pattern);
+
+        pattern
+    }
+
+    /// Returns neighbors of cell with given index taken
+    /// from `cells`. Neighbors without offset are replaced
+    /// with `ghost`.
+    pub fn neighbors_of<'a>(&'a self, index: usize, ghost: Option<&'a C>) -> Iter<'a, C> {
+        let pattern = self.patterns[index];
+        let offsets = &self.offsets[pattern];
+        let weights = &self.weights[pattern];
+        let keys = cell_keys(self.steps, 0, index);
+        Iter::new(&self.cells, &self.neighbors[index], offsets, weights, ghost, self.seed, keys)
+    }
+
+    /// Enables counting of cells changed by update. Then
+    /// every cell is compared with its old version, so
+    /// counting is off by default.
+    pub fn count_changes(&mut self)
+        where C: PartialEq,
+    {
+        self.compare = Some(differs::<C>);
+    }
+
+    /// Returns count of cells changed by last update
+    /// or `None` if counting is disabled.
+    pub fn changed(&self) -> Option<usize> { self.compare.map(|_| self.changed) }
+
+    /// Inits split border indices. Should be called
+    /// once all cells are pushed. Used later in `update`
+    /// to split `cells` between threads.
+    pub fn split(&mut self) {
+
+        let cells_count = self.cells.len();
+        let threads = self.pool.thread_count() as usize;
+        let cells_on_thread = cmp::max(cells_count / threads, 1);
+
+        self.parts.clear();
+
+        let mut start;
+        let mut end = 0;
+        // Emulating `do-while` loop.
+        while {
+            start = end;
+            end = cmp::min(start + cells_on_thread, cells_count);
+            self.parts.push((start, end));
+
+            end < cells_count
+        } {}
+    }
+
+    /// One step in evolution. Neighbors without offset
+    /// are replaced with `ghost`.
+    pub fn update<Es>(&mut self, ghost: Option<&C>, evolution_state: &Es)
+        where C: Cell<State = Es>,
+              Es: EvolutionState,
+    {
+        self.update_phases(ghost, evolution_state, &[pipeline::update]);
+    }
+
+    /// One step in evolution made of given phases. Every
+    /// phase is run over all cells at once. Whole step is
+    /// one generation for random streams, skipping and
+    /// counting of changed cells.
+    pub fn update_phases<Es>(&mut self,
+                             ghost: Option<&C>,
+                             evolution_state: &Es,
+                             phases: &[Phase<C>])
+        where C: Cell<State = Es>,
+              Es: EvolutionState,
+    {
+        let len = self.cells.len();
+        let initial = self.initial(phases);
+
+        for (index, &phase) in phases.iter().enumerate() {
+            self.run_range(ghost, evolution_state, 0, len, phase, index as u64);
+        }
+
+        if let Some(initial) = initial {
+            self.count_since(&initial);
+        }
+        self.steps += 1;
+    }
+
+    /// One step in evolution of cells with indices from `start`
+    /// to `end`. Other cells are left with values they had two
+    /// steps ago, so they should be set before the next step
+    /// if cells of range depend on them.
+    pub fn update_range<Es>(&mut self,
+                            ghost: Option<&C>,
+                            evolution_state: &Es,
+                            start: usize,
+                            end: usize,
+                            phase: Phase<C>)
+        where C: Cell<State = Es>,
+              Es: EvolutionState,
+    {
+        self.run_range(ghost, evolution_state, start, end, phase, 0);
+        self.steps += 1;
+    }
+
+    /// Runs phase with given index over cells from `start`
+    /// to `end` in threads of pool. Changed cells are counted
+    /// relative to the previous phase.
+    fn run_range<Es>(&mut self,
+                     ghost: Option<&C>,
+                     evolution_state: &Es,
+                     start: usize,
+                     end: usize,
+                     phase: Phase<C>,
+                     phase_index: u64)
+        where C: Cell<State = Es>,
+              Es: EvolutionState,
+    {
+        mem::swap(&mut self.cells, &mut self.old_cells);
+
+        let mut cells = MutPtr(&mut *self.cells as *mut [C]);
+        let old_cells = ConstPtr(&*self.old_cells as *const [C]);
+        let neighbors = ConstPtr(&*self.neighbors as *const [Vec<Option<usize>>]);
+        let patterns = ConstPtr(&*self.patterns as *const [usize]);
+        let offsets = ConstPtr(&*self.offsets as *const [Vec<Offset>]);
+        let weights = ConstPtr(&*self.weights as *const [Vec<f64>]);
+        let evolution_state = ConstPtr(evolution_state as *const Es);
+        let ghost = ghost.map(|ghost| ConstPtr(ghost as *const C));
+        let (seed, steps, probability) = (self.seed, self.steps, self.probability);
+        let compare = self.compare;
+
+        let range = (start, end);
+        let parts: Vec<_> = self.parts
+            .iter()
+            .map(|&(start, end)| (cmp::max(start, range.0), cmp::min(end, range.1)))
+            .filter(|&(start, end)| start < end)
+            .collect();
+        let mut changed = vec![0; parts.len()];
+
+        self.pool.scoped(|scope| {
+            for (&(start, end), changed) in parts.iter().zip(changed.iter_mut()) {
+                scope.execute(move || {
+                    for i in start..end {
+                        unsafe {
+                            let neighbors = neighbors.get_unchecked(i);
+                            let pattern = *patterns.get_unchecked(i);
+                            let offsets = offsets.get_unchecked(pattern);
+                            let weights = weights.get_unchecked(pattern);
+                            let ghost = ghost.as_ref().map(|ghost| &**ghost);
+                            let keys = cell_keys(steps, phase_index, i);
+                            let neighbors_iter = Iter::new(&*old_cells,
+                                                           neighbors,
+                                                           offsets,
+                                                           weights,
+                                                           ghost,
+                                                           seed,
+                                                           keys);
+
+                            let old = (*old_cells).get_unchecked(i);
+                            let cell = (*cells).get_unchecked_mut(i);
+
+                            if skips(seed, steps, i, probability) {
+                                cell.clone_from(old);
+                            } else {
+                                phase(cell, old, neighbors_iter, &*evolution_state);
+                            }
+
+                            if compare.map_or(false, |differs| differs(cell, old)) {
+                                *changed += 1;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        self.changed = changed.iter().fold(0, |sum, changed| sum + changed);
+    }
+
+    /// One step in evolution where cells are updated one
+    /// at a time in given order. Every cell sees new values
+    /// of neighbors updated before it. Every phase goes over
+    /// all cells. `order` should contain every index once.
+    pub fn update_in_order<Es>(&mut self,
+                               ghost: Option<&C>,
+                               evolution_state: &Es,
+                               order: &[usize],
+                               phases: &[Phase<C>])
+        where C: Cell<State = Es>,
+              Es: EvolutionState,
+    {
+        let initial = self.initial(phases);
+        self.changed = 0;
+
+        for (phase_index, &phase) in phases.iter().enumerate() {
+            self.old_cells.clone_from(&self.cells);
+
+            for &i in order {
+                if skips(self.seed, self.steps, i, self.probability) {
+                    continue;
+                }
+
+                let mut cell = self.old_cells[i].clone();
+
+                {
+                    let pattern = self.patterns[i];
+                    let offsets = &self.offsets[pattern];
+                    let weights = &self.weights[pattern];
+                    let keys = cell_keys(self.steps, phase_index as u64, i);
+                    let neighbors = Iter::new(&self.cells,
+                                              &self.neighbors[i],
+                                              offsets,
+                                              weights,
+                                              ghost,
+                                              self.seed,
+                                              keys);
+                    phase(&mut cell, &self.old_cells[i], neighbors, evolution_state);
+                }
+
+                if self.compare.map_or(false, |differs| differs(&cell, &self.old_cells[i])) {
+                    self.changed += 1;
+                }
+                self.cells[i] = cell;
+            }
+        }
+
+        if let Some(initial) = initial {
+            self.count_since(&initial);
+        }
+        self.steps += 1;
+    }
+
+    /// Copies cells before generation of several phases
+    /// if changes are counted. Changes made by single phase
+    /// are counted while cells are updated.
+    fn initial(&self, phases: &[Phase<C>]) -> Option<Vec<C>> {
+        assert!(!phases.is_empty(), "Generation should have at least one phase.");
+
+        if phases.len() > 1 && self.compare.is_some() {
+            Some(self.cells.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Counts cells which differ from given ones.
+    fn count_since(&mut self, initial: &[C]) {
+        if let Some(differs) = self.compare {
+            self.changed = self.cells
+                .iter()
+                .zip(initial.iter())
+                .filter(|&(cell, old)| differs(cell, old))
+                .count();
+        }
+    }
+}
+
+
+/// Compares cells for `DoubleBuffer::count_changes`.
+pub fn differs<C: PartialEq>(cell: &C, old: &C) -> bool { cell != old }
+
+/// Keys of random stream of cell with given index
+/// in given phase of given step.
+fn cell_keys(steps: u64, phase: u64, index: usize) -> [u64; 3] { [steps, phase, index as u64] }
+
+/// Decides whether cell is left as is on given step. Decision
+/// is the same for all phases of step. Uses stream other
+/// than streams of cell.
+fn skips(seed: u64, steps: u64, index: usize, probability: f64) -> bool {
+    probability < 1.0 && Rng::from_keys(!seed, &[steps, index as u64]).next_f64() >= probability
+}
+
+/// Helper struct to enable sendable mutable pointers.
+struct MutPtr<T: ?Sized>(*mut T);
+
+unsafe impl<T: ?Sized> Send for MutPtr<T> {}
+unsafe impl<T: ?Sized> Sync for MutPtr<T> {}
+
+impl<T: ?Sized> Deref for MutPtr<T> {
+    type Target = T;
+    fn deref(&self) -> &T { unsafe { &*self.0 } }
+}
+
+impl<T: ?Sized> DerefMut for MutPtr<T> {
+    fn deref_mut(&mut self) -> &mut T { unsafe { &mut *self.0 } }
+}
+
+#[allow(expl_impl_clone_on_copy)]
+impl<T: ?Sized> Clone for MutPtr<T> {
+    fn clone(&self) -> Self { MutPtr(self.0) }
+}
+
+impl<T: ?Sized> Copy for MutPtr<T> {}
+
+/// Helper struct to enable sendable const pointers.
+struct ConstPtr<T: ?Sized>(*const T);
+
+unsafe impl<T: ?Sized> Send for ConstPtr<T> {}
+unsafe impl<T: ?Sized> Sync for ConstPtr<T> {}
+
+impl<T: ?Sized> Deref for ConstPtr<T> {
+    type Target = T;
+    fn deref(&self) -> &T { unsafe { &*self.0 } }
+}
+
+#[allow(expl_impl_clone_on_copy)]
+impl<T: ?Sized> Clone for ConstPtr<T> {
+    fn clone(&self) -> Self { ConstPtr(self.0) }
+}
+
+impl<T: ?Sized> Copy for ConstPtr<T> {}
@@ -0,0 +1,27 @@
+use traits::Coord;
+
+
+/// Coordinate for `GraphGrid`: id of node. It is
+/// stored in `x` component, other ones are always 0.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct NodeCoord {
+    id: u32,
+}
+
+
+impl NodeCoord {
+    /// Constructs coord of node with given id.
+    pub fn new(id: u32) -> Self { NodeCoord { id: id } }
+
+    /// Returns id of node.
+    pub fn id(&self) -> u32 { self.id }
+}
+
+
+impl Coord for NodeCoord {
+    fn from_2d(x: i32, _: i32) -> Self { NodeCoord { id: x as u32 } }
+
+    fn x(&self) -> i32 { self.id as i32 }
+
+    fn y(&self) -> i32 { 0 }
+}
@@ -0,0 +1,142 @@
+//! Grid for automata on arbitrary graphs.
+
+mod coord;
+mod topology;
+mod test;
+
+use traits::Grid;
+use traits::Cell;
+use traits::EvolutionState;
+use traits::Nhood;
+use traits::Coord;
+
+use super::buffer::DoubleBuffer;
+pub use self::coord::NodeCoord;
+pub use self::topology::Graph;
+
+/// Grid where cells are nodes of graph. Topology is given
+/// by neighborhood (usually `Graph`), so every node can have
+/// its own count of neighbors. Neighbors which are not nodes
+/// of grid are `None`. Offsets of neighbors are given by
+/// neighborhood; `Graph` gives ordinals of neighbors.
+/// Update process is the same as in `TwodimGrid`.
+pub struct GraphGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = NodeCoord>,
+          Es: EvolutionState,
+{
+    buffer: DoubleBuffer<C>,
+    evolution_state: Es,
+    nhood: N,
+    nodes: u32,
+}
+
+
+impl<C, N, Es> GraphGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = NodeCoord>,
+          Es: EvolutionState,
+{
+    /// Constructs GraphGrid with given count of nodes, neighborhood
+    /// strategy, initial evolution state, threads count.
+    pub fn new(nodes: u32, nhood: N, state: C::State, threads: u32) -> Self {
+
+        let mut grid = GraphGrid {
+            buffer: DoubleBuffer::new(nodes as usize, threads),
+            evolution_state: state,
+            nhood: nhood,
+            nodes: nodes,
+        };
+
+        grid.init();
+
+        grid
+    }
+
+    /// Enables counting of changed cells (see `Grid::changed`).
+    pub fn count_changes(&mut self)
+        where C: PartialEq,
+    {
+        self.buffer.count_changes();
+    }
+
+    fn init(&mut self) {
+
+        for id in 0..self.nodes {
+
+            let coord = NodeCoord::new(id);
+
+            let neighbors = self.get_neighbors(&coord);
+            let offsets = self.nhood.offsets(&coord);
+            let weights = self.nhood.weights(&coord);
+            let cell = C::with_coord(coord);
+
+            self.buffer.push(cell, neighbors, offsets, weights);
+        }
+
+        self.buffer.split();
+    }
+
+    fn get_neighbors(&self, coord: &NodeCoord) -> Vec<Option<usize>> {
+
+        let nodes = self.nodes as i32;
+
+        self.nhood
+            .neighbors(coord)
+            .iter()
+            .map(|coord| if coord.x() >= 0 && coord.x() < nodes {
+                Some(coord.x() as usize)
+            } else {
+                None
+            })
+            .collect()
+    }
+}
+
+
+impl<C, Es> GraphGrid<C, Graph, Es>
+    where C: Cell<State = Es> + Clone,
+          Es: EvolutionState,
+{
+    /// Constructs GraphGrid with all nodes of given graph.
+    pub fn from_graph(graph: Graph, state: C::State, threads: u32) -> Self {
+        let nodes = graph.nodes();
+        GraphGrid::new(nodes, graph, state, threads)
+    }
+}
+
+
+impl<C, N, Es> Grid for GraphGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = NodeCoord>,
+          Es: EvolutionState + Send,
+{
+    type Cell = C;
+    type Coord = NodeCoord;
+
+    fn update(&mut self) {
+        self.buffer.update(None, &self.evolution_state);
+        self.evolution_state.update();
+    }
+
+    fn set_cells(&mut self, new_cells: Vec<Self::Cell>) {
+
+        for cell in new_cells.into_iter() {
+            let index = cell.coord().x() as usize;
+            self.buffer.cells[index] = cell;
+        }
+    }
+
+    fn cells(&self) -> &[Self::Cell] { &self.buffer.cells }
+
+    fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { &self.evolution_state }
+
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) {
+        self.evolution_state = state;
+    }
+
+    /// Returns count of nodes as `x`.
+    fn size(&self) -> Self::Coord { NodeCoord::new(self.nodes) }
+
+    fn changed(&self) -> Option<usize> { self.buffer.changed() }
+}
@@ -0,0 +1,80 @@
+#![cfg(test)]
+use traits::Cell;
+use traits::Neighbors;
+use traits::Coord;
+use traits::Grid;
+use grid::graph::Graph;
+use grid::graph::GraphGrid;
+use grid::EmptyState;
+
+/// SI epidemic: susceptible node gets infected
+/// if any of its neighbors is infected.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Infection {
+    coord: (i32, i32),
+    infected: bool,
+    degree: usize,
+}
+
+impl Cell for Infection {
+    type Coord = (i32, i32);
+    type State = EmptyState;
+
+    fn update<'a, I>(&'a mut self, old: &'a Self, neighbors: I, _: &Self::State)
+        where I: Neighbors<'a, Self>,
+    {
+        let neighbors: Vec<_> = neighbors.map(|n| n.expect("all neighbors are nodes")).collect();
+
+        self.degree = neighbors.len();
+        self.infected = old.infected || neighbors.iter().any(|n| n.infected);
+    }
+
+    fn with_coord<C: Coord>(coord: C) -> Self {
+        Infection {
+            coord: (coord.x(), coord.y()),
+            infected: false,
+            degree: 0,
+        }
+    }
+
+    fn coord(&self) -> &Self::Coord { &self.coord }
+
+    fn set_coord<C: Coord>(&mut self, coord: &C) { self.coord = (coord.x(), coord.y()); }
+}
+
+
+fn infected<G: Grid<Cell = Infection>>(grid: &G) -> Vec<i32> {
+    grid.cells().iter().filter(|c| c.infected).map(|c| c.coord.x()).collect()
+}
+
+
+#[test]
+fn test_star_and_chain() {
+    // Star with center 0 and leaves 1..4, leaf 4
+    // starts a chain 4 - 5 - 6.
+    let mut graph = Graph::new(7);
+    for leaf in 1..5 {
+        graph.add_edge(0, leaf);
+    }
+    graph.add_edge(4, 5);
+    graph.add_edge(5, 6);
+
+    let mut grid: GraphGrid<Infection, _, _> = GraphGrid::from_graph(graph, EmptyState, 2);
+
+    let mut patient_zero = Infection::with_coord((6, 0));
+    patient_zero.infected = true;
+    grid.set_cells(vec![patient_zero]);
+
+    grid.update();
+    assert_eq!(infected(&grid), vec![5, 6]);
+
+    let degrees: Vec<_> = grid.cells().iter().map(|c| c.degree).collect();
+    assert_eq!(degrees, vec![4, 1, 1, 1, 2, 2, 1]);
+
+    grid.update();
+    grid.update();
+    assert_eq!(infected(&grid), vec![0, 4, 5, 6]);
+
+    grid.update();
+    assert_eq!(infected(&grid), vec![0, 1, 2, 3, 4, 5, 6]);
+}
@@ -0,0 +1,199 @@
+use std::fs::File;
+use std::i32;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::path::Path;
+
+use traits::Nhood;
+use traits::Coord;
+use traits::Offset;
+
+use super::coord::NodeCoord;
+
+
+/// Count of nodes should fit into `i32`, since id
+/// of node is `x` of its coord.
+const MAX_NODES: u32 = i32::MAX as u32;
+
+/// Count of nodes in edge list may exceed count of edge
+/// ends at most by this, so few lines can't make graph
+/// of billions of isolated nodes.
+const MAX_ISOLATED: u64 = 1 << 16;
+
+
+/// Graph stored as adjacency list. Neighbors of node
+/// are returned in order edges were added.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Graph {
+    adjacency: Vec<Vec<u32>>,
+}
+
+
+impl Graph {
+    /// Constructs graph with given count of nodes and without edges.
+    pub fn new(nodes: u32) -> Self {
+        assert!(nodes <= MAX_NODES, "Count of nodes should fit into i32.");
+        Graph { adjacency: vec![Vec::new(); nodes as usize] }
+    }
+
+    /// Reads undirected graph from edge list: every line contains
+    /// ids of two nodes separated by whitespace. Empty lines and
+    /// lines starting with `#` are skipped. Count of nodes
+    /// is the biggest id plus one. Ids should fit into `i32`
+    /// and should not leave too many nodes without edges.
+    pub fn from_edge_list<R: BufRead>(reader: R) -> io::Result<Self> {
+
+        let mut edges = Vec::new();
+        let mut nodes = 0;
+
+        for (number, line) in reader.lines().enumerate() {
+
+            let line = try!(line);
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let ids: Vec<_> = line.split_whitespace().map(|id| id.parse::<u32>()).collect();
+
+            match (ids.get(0), ids.get(1), ids.len()) {
+                (Some(&Ok(a)), Some(&Ok(b)), 2) => {
+                    if a >= MAX_NODES || b >= MAX_NODES {
+                        let message = format!("too big node id on line {}", number + 1);
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+                    }
+
+                    nodes = *[nodes, a + 1, b + 1].iter().max().unwrap();
+                    edges.push((a, b));
+                },
+                _ => {
+                    let message = format!("malformed edge on line {}: {:?}", number + 1, line);
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+                },
+            }
+        }
+
+        if nodes as u64 > 2 * edges.len() as u64 + MAX_ISOLATED {
+            let message = format!("{} nodes for {} edges", nodes, edges.len());
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+
+        let mut graph = Graph::new(nodes);
+        for (a, b) in edges {
+            graph.add_edge(a, b);
+        }
+
+        Ok(graph)
+    }
+
+    /// Reads undirected graph from file with edge list.
+    /// See `from_edge_list` for format.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = try!(File::open(path));
+        Graph::from_edge_list(BufReader::new(file))
+    }
+
+    /// Adds node without edges. Returns its id.
+    pub fn add_node(&mut self) -> u32 {
+        assert!(self.nodes() < MAX_NODES, "Count of nodes should fit into i32.");
+        self.adjacency.push(Vec::new());
+        (self.adjacency.len() - 1) as u32
+    }
+
+    /// Adds undirected edge between two nodes.
+    pub fn add_edge(&mut self, a: u32, b: u32) {
+        self.add_arc(a, b);
+        if a != b {
+            self.add_arc(b, a);
+        }
+    }
+
+    /// Adds directed edge: `to` becomes neighbor of `from`.
+    pub fn add_arc(&mut self, from: u32, to: u32) { self.adjacency[from as usize].push(to); }
+
+    /// Returns count of nodes.
+    pub fn nodes(&self) -> u32 { self.adjacency.len() as u32 }
+
+    /// Returns ids of node's neighbors.
+    pub fn neighbors_of(&self, id: u32) -> &[u32] { &self.adjacency[id as usize] }
+}
+
+
+impl Nhood for Graph {
+    type Coord = NodeCoord;
+
+    fn neighbors(&self, coord: &Self::Coord) -> Vec<Self::Coord> {
+        self.adjacency
+            .get(coord.x() as usize)
+            .map(|ids| ids.iter().map(|&id| NodeCoord::new(id)).collect())
+            .unwrap_or_else(Vec::new)
+    }
+
+    /// Offset of neighbor is its ordinal `(i, 0, 0)`, since
+    /// ids of nodes don't say how they are placed. Cells see
+    /// neighbors in order edges were added, so nodes with
+    /// the same degree share pattern.
+    fn offsets(&self, coord: &Self::Coord) -> Vec<Offset> {
+        let degree = self.adjacency.get(coord.x() as usize).map_or(0, |ids| ids.len());
+        (0..degree).map(|i| (i as i32, 0, 0)).collect()
+    }
+
+    /// Returns the biggest degree of node in graph.
+    fn neighbors_count(&self) -> usize {
+        self.adjacency.iter().map(|ids| ids.len()).max().unwrap_or(0)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::Cursor;
+
+    use traits::Nhood;
+    use super::Graph;
+    use super::super::coord::NodeCoord;
+
+    #[test]
+    fn test_from_edge_list() {
+        let edges = "# triangle with tail\n0 1\n1 2\n\n2 0\n  2   3  \n";
+        let graph = Graph::from_edge_list(Cursor::new(edges)).unwrap();
+
+        assert_eq!(graph.nodes(), 4);
+        assert_eq!(graph.neighbors_of(0), &[1, 2]);
+        assert_eq!(graph.neighbors_of(2), &[1, 0, 3]);
+        assert_eq!(graph.neighbors_of(3), &[2]);
+        assert_eq!(graph.neighbors_count(), 3);
+
+        let neighbors = graph.neighbors(&NodeCoord::new(1));
+        assert_eq!(neighbors, vec![NodeCoord::new(0), NodeCoord::new(2)]);
+
+        assert_eq!(graph.offsets(&NodeCoord::new(2)), vec![(0, 0, 0), (1, 0, 0), (2, 0, 0)]);
+        assert_eq!(graph.offsets(&NodeCoord::new(0)), graph.offsets(&NodeCoord::new(1)));
+    }
+
+    #[test]
+    fn test_malformed_edge_list() {
+        assert!(Graph::from_edge_list(Cursor::new("0 1\n1\n")).is_err());
+        assert!(Graph::from_edge_list(Cursor::new("0 1 2\n")).is_err());
+        assert!(Graph::from_edge_list(Cursor::new("0 x\n")).is_err());
+        assert!(Graph::from_edge_list(Cursor::new("0 4294967295\n")).is_err());
+        assert!(Graph::from_edge_list(Cursor::new("0 2147483647\n")).is_err());
+        assert!(Graph::from_edge_list(Cursor::new("0 1000000\n")).is_err());
+        assert!(Graph::from_edge_list(Cursor::new("0 1000\n")).is_ok());
+    }
+
+    #[test]
+    fn test_build() {
+        let mut graph = Graph::new(2);
+        let c = graph.add_node();
+        graph.add_edge(0, c);
+        graph.add_arc(1, 0);
+
+        assert_eq!(graph.neighbors_of(0), &[2]);
+        assert_eq!(graph.neighbors_of(1), &[0]);
+        assert_eq!(graph.neighbors_of(2), &[0]);
+    }
+}
@@ -0,0 +1,270 @@
+//! HashLife grid for Life-like automata.
+//!
+//! Plane is stored as quadtree of canonical nodes, so
+//! repeated parts of pattern (in space and in time)
+//! are stored and computed only once. It allows to
+//! advance patterns by huge number of generations at once.
+
+mod rule;
+mod universe;
+
+use traits::Grid;
+use traits::Cell;
+use traits::Coord;
+use grid::twodim::GridCoord;
+
+use self::universe::NodeId;
+use self::universe::Universe;
+use self::universe::MAX_LEVEL;
+pub use self::rule::LifeRule;
+
+
+/// Cell with only two states. Only such cells can be
+/// evolved with HashLife, since whole evolution is
+/// defined by `LifeRule` rather than by `Cell::update`.
+pub trait BinaryCell: Cell {
+    /// Returns `true` if cell is alive.
+    fn is_alive(&self) -> bool;
+    /// Changes state of cell.
+    fn set_alive(&mut self, alive: bool);
+}
+
+
+/// Largest `k` of single step by `2^k` generations.
+const MAX_STEP: u8 = MAX_LEVEL - 3;
+
+
+/// Unbounded grid evolved with HashLife algorithm.
+/// `cells` returns alive cells only. `Cell::update` is never
+/// called and evolution state is never updated.
+/// Coords of cells should fit into square with side `2^63`.
+///
+/// Memoized nodes are kept until `clear_cache` is called,
+/// or until there are more of them than cache limit.
+pub struct HashLifeGrid<C: BinaryCell> {
+    universe: Universe,
+    root: NodeId,
+    cache_limit: Option<usize>,
+    generation: u64,
+    evolution_state: C::State,
+    cells: Vec<C>,
+}
+
+
+impl<C: BinaryCell> HashLifeGrid<C> {
+    /// Constructs empty grid with given rule and evolution state.
+    pub fn new(rule: LifeRule, state: C::State) -> Self {
+
+        let mut universe = Universe::new(rule);
+        let root = universe.empty(3);
+
+        HashLifeGrid {
+            universe: universe,
+            root: root,
+            cache_limit: None,
+            generation: 0,
+            evolution_state: state,
+            cells: Vec::new(),
+        }
+    }
+
+    /// Constructs grid with alive cells of another grid.
+    pub fn from_grid<G>(grid: &G, rule: LifeRule, state: C::State) -> Self
+        where G: Grid<Cell = C>,
+              C: Clone,
+    {
+        let mut hashlife = HashLifeGrid::new(rule, state);
+
+        let alive = grid.cells().iter().filter(|cell| cell.is_alive()).cloned().collect();
+        hashlife.set_cells(alive);
+
+        hashlife
+    }
+
+    /// Changes all cells of another grid, so they
+    /// match cells of this grid.
+    pub fn export<G>(&self, grid: &mut G)
+        where G: Grid<Cell = C>,
+              C: Clone,
+    {
+        let cells = grid.cells()
+            .iter()
+            .map(|cell| {
+                let mut cell = cell.clone();
+                let alive = self.is_alive(cell.coord().x(), cell.coord().y());
+                cell.set_alive(alive);
+
+                cell
+            })
+            .collect();
+
+        grid.set_cells(cells);
+    }
+
+    /// Returns `true` if cell with given coord is alive.
+    pub fn is_alive(&self, x: i32, y: i32) -> bool {
+        match self.to_root(x as i64, y as i64) {
+            Some((x, y)) => self.universe.get(self.root, x, y),
+            None => false,
+        }
+    }
+
+    /// Returns count of alive cells.
+    pub fn population(&self) -> u64 { self.universe.population(self.root) }
+
+    /// Returns count of generations passed.
+    pub fn generation(&self) -> u64 { self.generation }
+
+    /// Drops memoized results and nodes which are not
+    /// part of current pattern.
+    pub fn clear_cache(&mut self) { self.root = self.universe.compact(self.root); }
+
+    /// Limits count of stored nodes. Cache is cleared when
+    /// it grows over limit after step. Cache is not limited
+    /// by default.
+    pub fn set_cache_limit(&mut self, nodes: usize) { self.cache_limit = Some(nodes); }
+
+    /// Advances grid by `2^k` generations at once.
+    /// `k` should not be greater than 63.
+    pub fn step_pow2(&mut self, k: u8) {
+        assert!(k < 64, "Step should be at most 2^63 generations.");
+
+        self.step_large(k);
+        self.collect_cells();
+    }
+
+    /// Advances grid by `generations` generations. Count
+    /// is split into powers of 2, so it takes `log2(generations)`
+    /// steps at most.
+    pub fn advance(&mut self, generations: u64) {
+        for k in 0..64 {
+            if generations & (1 << k) != 0 {
+                self.step_large(k);
+            }
+        }
+        self.collect_cells();
+    }
+
+    /// Steps by more than `2^MAX_STEP` generations are
+    /// split into several steps.
+    fn step_large(&mut self, k: u8) {
+        if k <= MAX_STEP {
+            return self.step(k);
+        }
+
+        for _ in 0..1u64 << (k - MAX_STEP) {
+            self.step(MAX_STEP);
+        }
+    }
+
+    fn step(&mut self, k: u8) {
+
+        // Result of step is the center of root, so pattern
+        // should be small enough to not leave it.
+        while self.universe.level(self.root) < k + 3 || !self.is_centered() {
+            self.root = self.universe.expand(self.root);
+        }
+
+        self.root = self.universe.step(self.root, k);
+        self.generation = self.generation.wrapping_add(1 << k);
+
+        if let Some(limit) = self.cache_limit {
+            if self.universe.len() > limit {
+                self.clear_cache();
+            }
+        }
+    }
+
+    /// Checks if all alive cells are in the central
+    /// quarter of root.
+    fn is_centered(&mut self) -> bool {
+        let center = self.universe.center(self.root);
+        let center = self.universe.center(center);
+
+        self.universe.population(center) == self.population()
+    }
+
+    #[inline]
+    fn half(&self) -> i64 { 1 << (self.universe.level(self.root) - 1) }
+
+    /// Converts coord to coord relative to the top left
+    /// corner of root. Root is centered on the origin.
+    fn to_root(&self, x: i64, y: i64) -> Option<(u64, u64)> {
+        let half = self.half();
+
+        if x >= -half && x < half && y >= -half && y < half {
+            Some(((x + half) as u64, (y + half) as u64))
+        } else {
+            None
+        }
+    }
+
+    /// Rebuilds `cells` from alive cells of root.
+    /// Cells which coords do not fit into `i32` are skipped.
+    fn collect_cells(&mut self) {
+
+        let half = self.half();
+        let mut cells = Vec::with_capacity(self.population() as usize);
+
+        self.universe.alive(self.root, 0, 0, &mut |x, y| {
+            let x = x as i64 - half;
+            let y = y as i64 - half;
+
+            if x as i32 as i64 == x && y as i32 as i64 == y {
+                let mut cell = C::with_coord(GridCoord::from_2d(x as i32, y as i32));
+                cell.set_alive(true);
+                cells.push(cell);
+            }
+        });
+
+        self.cells = cells;
+    }
+}
+
+
+impl<C: BinaryCell> Grid for HashLifeGrid<C> {
+    type Cell = C;
+    type Coord = GridCoord;
+
+    fn update(&mut self) { self.step_pow2(0); }
+
+    fn set_cells(&mut self, new_cells: Vec<Self::Cell>) {
+
+        for cell in &new_cells {
+            let x = cell.coord().x() as i64;
+            let y = cell.coord().y() as i64;
+
+            while self.to_root(x, y).is_none() {
+                self.root = self.universe.expand(self.root);
+            }
+
+            let (x, y) = self.to_root(x, y).unwrap();
+            self.root = self.universe.set(self.root, x, y, cell.is_alive());
+        }
+
+        self.collect_cells();
+    }
+
+    /// Only alive cells are returned by `cells`, so
+    /// grid is cleared before cells are set.
+    fn replace_cells(&mut self, cells: Vec<Self::Cell>) {
+        self.root = self.universe.empty(3);
+        self.set_cells(cells);
+    }
+
+    fn cells(&self) -> &[Self::Cell] { &self.cells }
+
+    fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { &self.evolution_state }
+
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) {
+        self.evolution_state = state;
+    }
+
+    /// Returns side of square covered by quadtree.
+    fn size(&self) -> Self::Coord {
+        let level = self.universe.level(self.root);
+        let side = if level > 30 { i32::max_value() } else { 1 << level };
+
+        GridCoord::from_2d(side, side)
+    }
+}
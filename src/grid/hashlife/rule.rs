@@ -0,0 +1,90 @@
+/// Rule of Life-like automaton: two states and
+/// Moore neighborhood. Next state depends only on
+/// current state and count of alive neighbors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LifeRule {
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+
+impl LifeRule {
+    /// Conway's Game of Life (B3/S23).
+    pub fn conway() -> Self { LifeRule::parse("B3/S23").unwrap() }
+
+    /// Parses rule written in B/S notation, i.e. `B36/S23`.
+    /// Returns `None` if rule is malformed or has birth on 0
+    /// neighbors, since HashLife assumes dead space stays dead.
+    pub fn parse(rule: &str) -> Option<Self> {
+
+        let mut parts = rule.split('/');
+
+        let birth = parts.next().and_then(|part| parse_counts(part, 'B'));
+        let survival = parts.next().and_then(|part| parse_counts(part, 'S'));
+
+        match (birth, survival, parts.next()) {
+            (Some(birth), Some(survival), None) if !birth[0] => {
+                Some(LifeRule {
+                    birth: birth,
+                    survival: survival,
+                })
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns next state of cell.
+    #[inline]
+    pub fn next(&self, alive: bool, alive_neighbors: usize) -> bool {
+        if alive {
+            self.survival[alive_neighbors]
+        } else {
+            self.birth[alive_neighbors]
+        }
+    }
+}
+
+
+fn parse_counts(part: &str, prefix: char) -> Option<[bool; 9]> {
+
+    let mut chars = part.chars();
+    if chars.next().map(|c| c.to_ascii_uppercase()) != Some(prefix) {
+        return None;
+    }
+
+    let mut counts = [false; 9];
+    for c in chars {
+        match c.to_digit(10) {
+            Some(count) if count < 9 => counts[count as usize] = true,
+            _ => return None,
+        }
+    }
+
+    Some(counts)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::LifeRule;
+
+    #[test]
+    fn test_parse() {
+        let rule = LifeRule::parse("B36/S23").unwrap();
+
+        assert!(rule.next(false, 3));
+        assert!(rule.next(false, 6));
+        assert!(!rule.next(false, 2));
+        assert!(rule.next(true, 2));
+        assert!(!rule.next(true, 6));
+
+        assert!(LifeRule::parse("b3/s23").is_some());
+        assert!(LifeRule::parse("B3").is_none());
+        assert!(LifeRule::parse("S23/B3").is_none());
+        assert!(LifeRule::parse("B39/S23").is_none());
+        assert!(LifeRule::parse("B3/S23/C2").is_none());
+        assert!(LifeRule::parse("B03/S23").is_none());
+        assert!(LifeRule::parse("B0/S8").is_none());
+    }
+}
@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+
+use super::rule::LifeRule;
+
+
+/// Index of node in `Universe`.
+pub type NodeId = u32;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+/// Nodes of higher levels have sides which
+/// do not fit into `i64` coords of grid.
+pub const MAX_LEVEL: u8 = 63;
+
+
+/// Node of quadtree. Node of level `L` is a square
+/// with side `2^L`. Leaves (level 0) are single cells.
+struct Node {
+    // nw, ne, sw, se
+    children: [NodeId; 4],
+    level: u8,
+    population: u64,
+}
+
+
+/// Storage of canonical quadtree nodes. Equal nodes
+/// are stored only once, so results of evolution
+/// can be memoized per node.
+pub struct Universe {
+    nodes: Vec<Node>,
+    canonical: HashMap<[NodeId; 4], NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    empty: Vec<NodeId>,
+    rule: LifeRule,
+}
+
+
+impl Universe {
+    pub fn new(rule: LifeRule) -> Self {
+
+        let leaf = |population| {
+            Node {
+                children: [DEAD; 4],
+                level: 0,
+                population: population,
+            }
+        };
+
+        Universe {
+            nodes: vec![leaf(0), leaf(1)],
+            canonical: HashMap::new(),
+            results: HashMap::new(),
+            empty: vec![DEAD],
+            rule: rule,
+        }
+    }
+
+    #[inline]
+    pub fn level(&self, id: NodeId) -> u8 { self.nodes[id as usize].level }
+
+    #[inline]
+    pub fn population(&self, id: NodeId) -> u64 { self.nodes[id as usize].population }
+
+    #[inline]
+    fn children(&self, id: NodeId) -> [NodeId; 4] { self.nodes[id as usize].children }
+
+    /// Returns count of stored nodes.
+    pub fn len(&self) -> usize { self.nodes.len() }
+
+    /// Drops memoized results and nodes which are not
+    /// reachable from `root`. Returns id of `root` after that.
+    pub fn compact(&mut self, root: NodeId) -> NodeId {
+        let nodes = ::std::mem::replace(&mut self.nodes, Vec::new());
+
+        self.nodes.push(Node {
+            children: [DEAD; 4],
+            level: 0,
+            population: 0,
+        });
+        self.nodes.push(Node {
+            children: [DEAD; 4],
+            level: 0,
+            population: 1,
+        });
+        self.canonical.clear();
+        self.results.clear();
+        self.empty = vec![DEAD];
+
+        let mut moved = HashMap::new();
+        self.copy(&nodes, root, &mut moved)
+    }
+
+    /// Copies node with its children from old storage.
+    fn copy(&mut self, nodes: &[Node], id: NodeId, moved: &mut HashMap<NodeId, NodeId>) -> NodeId {
+        if id == DEAD || id == ALIVE {
+            return id;
+        }
+        if let Some(&copied) = moved.get(&id) {
+            return copied;
+        }
+
+        let children = nodes[id as usize].children;
+        let nw = self.copy(nodes, children[0], moved);
+        let ne = self.copy(nodes, children[1], moved);
+        let sw = self.copy(nodes, children[2], moved);
+        let se = self.copy(nodes, children[3], moved);
+
+        let copied = self.node(nw, ne, sw, se);
+        moved.insert(id, copied);
+
+        copied
+    }
+
+    /// Returns canonical node with given children.
+    pub fn node(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+
+        let children = [nw, ne, sw, se];
+
+        if let Some(&id) = self.canonical.get(&children) {
+            return id;
+        }
+
+        let population = children.iter().fold(0, |sum, &child| sum + self.population(child));
+        let node = Node {
+            children: children,
+            level: self.level(nw) + 1,
+            population: population,
+        };
+
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(node);
+        self.canonical.insert(children, id);
+
+        id
+    }
+
+    /// Returns node of given level without alive cells.
+    pub fn empty(&mut self, level: u8) -> NodeId {
+
+        while self.empty.len() <= level as usize {
+            let child = *self.empty.last().unwrap();
+            let id = self.node(child, child, child, child);
+            self.empty.push(id);
+        }
+
+        self.empty[level as usize]
+    }
+
+    /// Returns node of the next level with given node
+    /// in the center of it.
+    pub fn expand(&mut self, id: NodeId) -> NodeId {
+        let children = self.children(id);
+        let level = self.level(id);
+        assert!(level < MAX_LEVEL, "Pattern does not fit into HashLife grid.");
+
+        let empty = self.empty(level - 1);
+
+        let nw = self.node(empty, empty, empty, children[0]);
+        let ne = self.node(empty, empty, children[1], empty);
+        let sw = self.node(empty, children[2], empty, empty);
+        let se = self.node(children[3], empty, empty, empty);
+
+        self.node(nw, ne, sw, se)
+    }
+
+    /// Returns node of the previous level from
+    /// the center of given node.
+    pub fn center(&mut self, id: NodeId) -> NodeId {
+        let children = self.children(id);
+
+        let nw = self.children(children[0])[3];
+        let ne = self.children(children[1])[2];
+        let sw = self.children(children[2])[1];
+        let se = self.children(children[3])[0];
+
+        self.node(nw, ne, sw, se)
+    }
+
+    /// Returns state of cell. `x` and `y` are counted
+    /// from the top left corner of node.
+    pub fn get(&self, id: NodeId, x: u64, y: u64) -> bool {
+
+        let level = self.level(id);
+        if level == 0 {
+            return id == ALIVE;
+        }
+
+        let half = 1 << (level - 1);
+        let quadrant = ((y >= half) as usize) * 2 + (x >= half) as usize;
+        let child = self.children(id)[quadrant];
+
+        self.get(child, x % half, y % half)
+    }
+
+    /// Returns node with changed state of cell.
+    pub fn set(&mut self, id: NodeId, x: u64, y: u64, alive: bool) -> NodeId {
+
+        let level = self.level(id);
+        if level == 0 {
+            return if alive { ALIVE } else { DEAD };
+        }
+
+        let half = 1 << (level - 1);
+        let quadrant = ((y >= half) as usize) * 2 + (x >= half) as usize;
+
+        let mut children = self.children(id);
+        children[quadrant] = self.set(children[quadrant], x % half, y % half, alive);
+
+        self.node(children[0], children[1], children[2], children[3])
+    }
+
+    /// Calls `f` with coords of every alive cell.
+    /// Coords are counted from the top left corner of node.
+    pub fn alive<F: FnMut(u64, u64)>(&self, id: NodeId, x: u64, y: u64, f: &mut F) {
+
+        if self.population(id) == 0 {
+            return;
+        }
+
+        let level = self.level(id);
+        if level == 0 {
+            f(x, y);
+            return;
+        }
+
+        let half = 1 << (level - 1);
+        let children = self.children(id);
+
+        self.alive(children[0], x, y, f);
+        self.alive(children[1], x + half, y, f);
+        self.alive(children[2], x, y + half, f);
+        self.alive(children[3], x + half, y + half, f);
+    }
+
+    /// Returns center of given node (see `center`) advanced
+    /// by `2^k` generations. `k` should be at most `level - 2`.
+    pub fn step(&mut self, id: NodeId, k: u8) -> NodeId {
+
+        let level = self.level(id);
+        debug_assert!(level >= 2 && k <= level - 2);
+
+        if self.population(id) == 0 {
+            return self.empty(level - 1);
+        }
+
+        if let Some(&result) = self.results.get(&(id, k)) {
+            return result;
+        }
+
+        let result = if level == 2 {
+            self.step_base(id)
+        } else {
+            self.step_recursive(id, k)
+        };
+
+        self.results.insert((id, k), result);
+
+        result
+    }
+
+    /// Node of level 2 (4x4) is advanced by one generation directly.
+    fn step_base(&mut self, id: NodeId) -> NodeId {
+
+        let mut next = [DEAD; 4];
+
+        for (i, &(x, y)) in [(1, 1), (2, 1), (1, 2), (2, 2)].iter().enumerate() {
+
+            let mut alive_neighbors = 0;
+            for ny in y - 1..y + 2 {
+                for nx in x - 1..x + 2 {
+                    if (nx, ny) != (x, y) && self.get(id, nx, ny) {
+                        alive_neighbors += 1;
+                    }
+                }
+            }
+
+            if self.rule.next(self.get(id, x, y), alive_neighbors) {
+                next[i] = ALIVE;
+            }
+        }
+
+        self.node(next[0], next[1], next[2], next[3])
+    }
+
+    /// Node is split into 9 overlapping subnodes of the previous level.
+    /// Subnodes are advanced (or just centered for slow steps) and
+    /// combined into 4 nodes which are advanced again.
+    fn step_recursive(&mut self, id: NodeId, k: u8) -> NodeId {
+
+        let level = self.level(id);
+        let full_speed = k == level - 2;
+
+        let children = self.children(id);
+        let (nw, ne, sw, se) = (children[0], children[1], children[2], children[3]);
+
+        let n = self.horizontal(nw, ne);
+        let w = self.vertical(nw, sw);
+        let c = self.center(id);
+        let e = self.vertical(ne, se);
+        let s = self.horizontal(sw, se);
+
+        let subnodes = [nw, n, ne, w, c, e, sw, s, se];
+        let mut r = [DEAD; 9];
+        for (i, &subnode) in subnodes.iter().enumerate() {
+            r[i] = if full_speed {
+                self.step(subnode, k - 1)
+            } else {
+                self.center(subnode)
+            };
+        }
+
+        let k = if full_speed { k - 1 } else { k };
+
+        let nw = self.node(r[0], r[1], r[3], r[4]);
+        let ne = self.node(r[1], r[2], r[4], r[5]);
+        let sw = self.node(r[3], r[4], r[6], r[7]);
+        let se = self.node(r[4], r[5], r[7], r[8]);
+
+        let nw = self.step(nw, k);
+        let ne = self.step(ne, k);
+        let sw = self.step(sw, k);
+        let se = self.step(se, k);
+
+        self.node(nw, ne, sw, se)
+    }
+
+    /// Returns node between two nodes placed side by side.
+    fn horizontal(&mut self, w: NodeId, e: NodeId) -> NodeId {
+        let w = self.children(w);
+        let e = self.children(e);
+
+        self.node(w[1], e[0], w[3], e[2])
+    }
+
+    /// Returns node between two nodes placed one above another.
+    fn vertical(&mut self, n: NodeId, s: NodeId) -> NodeId {
+        let n = self.children(n);
+        let s = self.children(s);
+
+        self.node(n[2], n[3], s[0], s[1])
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::Universe;
+    use super::super::rule::LifeRule;
+
+    #[test]
+    fn test_canonical_nodes() {
+        let mut universe = Universe::new(LifeRule::conway());
+
+        let empty = universe.empty(3);
+        let a = universe.set(empty, 2, 5, true);
+        let b = universe.set(empty, 2, 5, true);
+
+        assert_eq!(a, b);
+        assert_eq!(universe.population(a), 1);
+        assert!(universe.get(a, 2, 5));
+        assert!(!universe.get(a, 5, 2));
+        assert_eq!(universe.set(a, 2, 5, false), empty);
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut universe = Universe::new(LifeRule::conway());
+
+        let mut node = universe.empty(4);
+        for x in 6..9 {
+            node = universe.set(node, x, 8, true);
+        }
+        universe.step(node, 2);
+        let len = universe.len();
+
+        let node = universe.compact(node);
+        assert!(universe.len() < len);
+        assert_eq!(universe.population(node), 3);
+        assert!(universe.get(node, 7, 8));
+        assert!(!universe.get(node, 7, 7));
+    }
+
+    #[test]
+    fn test_blinker() {
+        let mut universe = Universe::new(LifeRule::conway());
+
+        // Horizontal blinker in the center of 8x8 node.
+        let mut node = universe.empty(3);
+        for x in 3..6 {
+            node = universe.set(node, x, 4, true);
+        }
+
+        // Center of 8x8 node is 4x4 node, so blinker
+        // is shifted by 2 cells.
+        let next = universe.step(node, 0);
+        let mut alive = Vec::new();
+        universe.alive(next, 0, 0, &mut |x, y| alive.push((x, y)));
+        assert_eq!(alive, vec![(2, 1), (2, 2), (2, 3)]);
+
+        let next = universe.step(node, 1);
+        let mut alive = Vec::new();
+        universe.alive(next, 0, 0, &mut |x, y| alive.push((x, y)));
+        alive.sort();
+        assert_eq!(alive, vec![(1, 2), (2, 2), (3, 2)]);
+    }
+}
@@ -0,0 +1,88 @@
+use traits::Coord;
+
+
+/// Axial coordinate for `HexGrid`. `x` is column axis (`q`),
+/// `y` is row axis (`r`) and `z` is derived third cube
+/// axis, so `x + y + z == 0` always holds.
+/// Grid stores cells row by row with odd rows shoved right
+/// by a half of hex ("odd-r" layout), so coordinate can be
+/// converted to and from offset in one-dimensional `Vec`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct HexCoord {
+    q: i32,
+    r: i32,
+}
+
+
+impl HexCoord {
+    /// Constructs HexCoord from given offset (in one-dimensional
+    /// array or `Vec`) and grid size.
+    #[inline]
+    pub fn from_offset(offset: u32, rows: u32, cols: u32) -> HexCoord {
+        let col = (offset % cols) as i32;
+        let row = (offset / cols) as i32;
+
+        debug_assert!(row < rows as i32);
+
+        HexCoord {
+            q: col - (row - (row & 1)) / 2,
+            r: row,
+        }
+    }
+
+    /// Returns column and row of given axial coord
+    /// in "odd-r" layout.
+    #[inline]
+    pub fn to_col_row<C: Coord>(coord: &C) -> (i32, i32) {
+        let row = coord.y();
+        let col = coord.x() + (row - (row & 1)) / 2;
+
+        (col, row)
+    }
+}
+
+
+impl Coord for HexCoord {
+    fn from_2d(x: i32, y: i32) -> Self { HexCoord { q: x, r: y } }
+
+    fn x(&self) -> i32 { self.q }
+
+    fn y(&self) -> i32 { self.r }
+
+    fn z(&self) -> i32 { -self.q - self.r }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use traits::Coord;
+    use super::HexCoord;
+
+    #[test]
+    fn test_from_offset() {
+        let c = HexCoord::from_offset(0, 4, 4);
+        assert_eq!((c.x(), c.y(), c.z()), (0, 0, 0));
+
+        let c = HexCoord::from_offset(5, 4, 4);
+        assert_eq!((c.x(), c.y(), c.z()), (1, 1, -2));
+
+        let c = HexCoord::from_offset(8, 4, 4);
+        assert_eq!((c.x(), c.y(), c.z()), (-1, 2, -1));
+
+        let c = HexCoord::from_offset(15, 4, 4);
+        assert_eq!((c.x(), c.y(), c.z()), (2, 3, -5));
+    }
+
+    #[test]
+    fn test_to_col_row() {
+        for offset in 0..16 {
+            let c = HexCoord::from_offset(offset, 4, 4);
+            let (col, row) = HexCoord::to_col_row(&c);
+            assert_eq!((row * 4 + col) as u32, offset);
+        }
+
+        let outside = HexCoord::from_2d(0, -1);
+        assert_eq!(HexCoord::to_col_row(&outside), (-1, -1));
+    }
+}
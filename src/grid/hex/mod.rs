@@ -0,0 +1,155 @@
+//! Hexagonal grid with axial coordinates.
+
+mod coord;
+mod test;
+
+use traits::Grid;
+use traits::Cell;
+use traits::EvolutionState;
+use traits::Nhood;
+use traits::Coord;
+use grid::Boundary;
+
+use super::buffer::DoubleBuffer;
+pub use self::coord::HexCoord;
+
+/// Hexagonal grid. Cells are addressed with axial coords
+/// (see `HexCoord`) and stored row by row in one-dimensional `Vec`.
+/// Update process is the same as in `TwodimGrid`.
+/// Boundary policy is applied to column and row of neighbor,
+/// so wrapping policies need even count of rows.
+pub struct HexGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = HexCoord>,
+          Es: EvolutionState,
+{
+    buffer: DoubleBuffer<C>,
+    evolution_state: Es,
+    nhood: N,
+    boundary: Boundary<C>,
+    dimensions: HexCoord,
+    rows: u32,
+    cols: u32,
+}
+
+
+impl<C, N, Es> HexGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = HexCoord>,
+          Es: EvolutionState,
+{
+    /// Constructs HexGrid with given ROWSxCOLS, neighborhood
+    /// strategy, boundary policy, initial evolution state, threads count.
+    pub fn new(rows: u32,
+               cols: u32,
+               nhood: N,
+               boundary: Boundary<C>,
+               state: C::State,
+               threads: u32)
+               -> Self {
+
+        let len = (rows * cols) as usize;
+
+        let mut grid = HexGrid {
+            buffer: DoubleBuffer::new(len, threads),
+            evolution_state: state,
+            nhood: nhood,
+            boundary: boundary,
+            rows: rows,
+            cols: cols,
+            dimensions: HexCoord::from_2d(cols as i32, rows as i32),
+        };
+
+        grid.init();
+
+        grid
+    }
+
+    /// Enables counting of changed cells (see `Grid::changed`).
+    pub fn count_changes(&mut self)
+        where C: PartialEq,
+    {
+        self.buffer.count_changes();
+    }
+
+    fn init(&mut self) {
+
+        let cells_count = self.rows * self.cols;
+
+        for offset in 0..cells_count {
+
+            let coord = HexCoord::from_offset(offset, self.rows, self.cols);
+
+            let neighbors = self.get_neighbors(&coord);
+            let offsets = self.nhood.offsets(&coord);
+            let weights = self.nhood.weights(&coord);
+            let cell = C::with_coord(coord);
+
+            self.buffer.push(cell, neighbors, offsets, weights);
+        }
+
+        self.buffer.split();
+    }
+
+    fn get_neighbors(&self, coord: &HexCoord) -> Vec<Option<usize>> {
+
+        let neighbors_count = self.nhood.neighbors_count();
+        let mut neighbors = Vec::with_capacity(neighbors_count);
+
+        let cols = self.cols as i32;
+        let rows = self.rows as i32;
+
+        for coord in &self.nhood.neighbors(coord) {
+
+            let (col, row) = HexCoord::to_col_row(coord);
+            let resolved = self.boundary.resolve(col, row, cols, rows);
+            let neighbor = resolved.map(|(col, row)| (row * cols + col) as usize);
+
+            neighbors.push(neighbor);
+        }
+
+        neighbors
+    }
+
+    #[inline]
+    fn offset<Crd: Coord>(&self, coord: &Crd) -> usize {
+        let (col, row) = HexCoord::to_col_row(coord);
+
+        (row * self.cols as i32 + col) as usize
+    }
+}
+
+
+impl<C, N, Es> Grid for HexGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = HexCoord>,
+          Es: EvolutionState + Send,
+{
+    type Cell = C;
+    type Coord = HexCoord;
+
+    fn update(&mut self) {
+        self.buffer.update(self.boundary.ghost(), &self.evolution_state);
+        self.evolution_state.update();
+    }
+
+    fn set_cells(&mut self, new_cells: Vec<Self::Cell>) {
+
+        for cell in new_cells.into_iter() {
+            let index = self.offset(cell.coord());
+            self.buffer.cells[index] = cell;
+        }
+    }
+
+    fn cells(&self) -> &[Self::Cell] { &self.buffer.cells }
+
+    fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { &self.evolution_state }
+
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) {
+        self.evolution_state = state;
+    }
+
+    fn size(&self) -> Self::Coord { self.dimensions.clone() }
+
+    fn changed(&self) -> Option<usize> { self.buffer.changed() }
+}
@@ -0,0 +1,92 @@
+#![cfg(test)]
+use traits::Cell;
+use traits::Neighbors;
+use traits::Coord;
+use traits::Grid;
+use grid::nhood::HexNhood;
+use grid::hex::HexGrid;
+use grid::Boundary;
+use grid::EmptyState;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct HexTestCell {
+    coord: (i32, i32),
+    neighbors: usize,
+}
+
+impl Cell for HexTestCell {
+    type Coord = (i32, i32);
+    type State = EmptyState;
+
+    fn update<'a, I>(&'a mut self, _: &'a Self, neighbors: I, _: &Self::State)
+        where I: Neighbors<'a, Self>,
+    {
+        self.neighbors = neighbors.filter(|n| n.is_some()).count();
+    }
+
+    fn with_coord<C: Coord>(coord: C) -> Self {
+        HexTestCell {
+            coord: (coord.x(), coord.y()),
+            neighbors: 0,
+        }
+    }
+
+    fn coord(&self) -> &Self::Coord { &self.coord }
+
+    fn set_coord<C: Coord>(&mut self, coord: &C) { self.coord = (coord.x(), coord.y()); }
+}
+
+
+#[test]
+fn test_open_neighbors() {
+    // Odd rows are shoved right.
+    //  0 | 1 | 2
+    //    3 | 4 | 5
+    //  6 | 7 | 8
+    let nhood = HexNhood::new();
+    let mut grid: HexGrid<HexTestCell, _, _> =
+        HexGrid::new(3, 3, nhood, Boundary::Open, EmptyState, 1);
+    grid.update();
+
+    let counts: Vec<usize> = grid.cells().iter().map(|c| c.neighbors).collect();
+    assert_eq!(counts, vec![2, 4, 3, 5, 6, 3, 2, 4, 3]);
+
+    // Center cell of odd row sees upper and lower cells
+    // right above and below it and to the right of it.
+    assert_eq!(grid.buffer.neighbors[4],
+               vec![Some(1), Some(2), Some(3), Some(5), Some(7), Some(8)]);
+}
+
+
+#[test]
+fn test_torus_neighbors() {
+    let nhood = HexNhood::new();
+    let mut grid: HexGrid<HexTestCell, _, _> =
+        HexGrid::new(4, 4, nhood, Boundary::Torus, EmptyState, 2);
+    grid.update();
+
+    assert!(grid.cells().iter().all(|c| c.neighbors == 6));
+
+    for neighbors in &grid.buffer.neighbors {
+        let mut neighbors = neighbors.clone();
+        neighbors.sort();
+        neighbors.dedup();
+        assert_eq!(neighbors.len(), 6);
+    }
+}
+
+
+#[test]
+fn test_set_cells() {
+    let nhood = HexNhood::new();
+    let mut grid: HexGrid<HexTestCell, _, _> =
+        HexGrid::new(4, 4, nhood, Boundary::Open, EmptyState, 1);
+
+    // Axial coord (-1, 2) is first cell of third row.
+    grid.set_cells(vec![HexTestCell {
+                            coord: (-1, 2),
+                            neighbors: 42,
+                        }]);
+
+    assert_eq!(grid.cells()[8].neighbors, 42);
+}
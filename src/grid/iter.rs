@@ -0,0 +1,84 @@
+use traits::Cell;
+use traits::Neighbors;
+use traits::Offset;
+use rng::Rng;
+
+
+/// Iterator over neighbors of cell. Neighbors
+/// are given as offsets in `cells`. Neighbor
+/// without offset is replaced with `ghost`.
+/// Offsets and weights of neighbors are carried along.
+/// Random stream of cell is made from seed and keys
+/// only when cell asks for it.
+pub struct Iter<'a, C: 'a> {
+    cells: &'a [C],
+    neighbors: &'a [Option<usize>],
+    offsets: &'a [Offset],
+    weights: &'a [f64],
+    ghost: Option<&'a C>,
+    seed: u64,
+    keys: [u64; 3],
+    index: usize,
+}
+
+
+impl<'a, C> Iter<'a, C> {
+    /// Constructs iterator over `neighbors` of cell taken
+    /// from `cells`. Random stream of cell is derived
+    /// from `seed` and `keys` (see `Rng::from_keys`).
+    pub fn new(cells: &'a [C],
+               neighbors: &'a [Option<usize>],
+               offsets: &'a [Offset],
+               weights: &'a [f64],
+               ghost: Option<&'a C>,
+               seed: u64,
+               keys: [u64; 3])
+               -> Self {
+
+        Iter {
+            cells: cells,
+            neighbors: neighbors,
+            offsets: offsets,
+            weights: weights,
+            ghost: ghost,
+            seed: seed,
+            keys: keys,
+            index: 0,
+        }
+    }
+}
+
+
+impl<'a, C: Cell> Iterator for Iter<'a, C> {
+    type Item = Option<&'a C>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+
+        let next = match self.index {
+            i if i < self.neighbors.len() => {
+
+                let maybe_index = self.neighbors[i];
+                match maybe_index {
+                    Some(index) => Some(Some(&self.cells[index])),
+                    None => Some(self.ghost),
+                }
+            },
+            _ => {
+                self.index = 0;
+                None
+            },
+        };
+        self.index += 1;
+
+        next
+    }
+}
+
+
+impl<'a, C: Cell> Neighbors<'a, C> for Iter<'a, C> {
+    fn offsets(&self) -> &'a [Offset] { self.offsets }
+
+    fn weights(&self) -> &'a [f64] { self.weights }
+
+    fn rng(&self) -> Rng { Rng::from_keys(self.seed, &self.keys) }
+}
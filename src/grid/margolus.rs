@@ -0,0 +1,338 @@
+//! 2D grid updated by 2x2 blocks (Margolus neighborhood).
+
+use std::mem;
+
+use traits::Grid;
+use traits::Cell;
+use traits::EvolutionState;
+use traits::Coord;
+use grid::Boundary;
+use grid::twodim::GridCoord;
+
+use super::buffer::differs;
+
+
+/// Evolution state of automata updated by blocks.
+/// It tracks phase of partition which should be
+/// changed on every `update`.
+pub trait BlockState: EvolutionState {
+    /// Blocks of even phase start at `(0, 0)`,
+    /// blocks of odd phase start at `(1, 1)`.
+    fn phase(&self) -> u32;
+}
+
+
+/// Rule which transforms whole block at once. Block is
+/// given as `[top-left, top-right, bottom-left, bottom-right]`.
+/// Rule may freely move cells within block, grid restores
+/// their coords afterwards.
+pub trait BlockRule<C: Cell> {
+    /// Transforms block in place.
+    fn update(&self, block: &mut [C; 4], state: &C::State);
+}
+
+
+/// 2D grid partitioned into 2x2 blocks. Partition is
+/// shifted by one cell on both axes every other phase.
+/// `Cell::update` is not used, cells are changed by `BlockRule`.
+/// With `Boundary::Torus` blocks crossing edges wrap around
+/// (if grid has even size on that axis), with any other boundary
+/// such blocks are left unchanged.
+pub struct MargolusGrid<C, R, Es>
+    where C: Cell<State = Es> + Clone,
+          R: BlockRule<C>,
+          Es: BlockState,
+{
+    cells: Vec<C>,
+    evolution_state: Es,
+    rule: R,
+    boundary: Boundary<C>,
+    dimensions: GridCoord,
+    rows: u32,
+    cols: u32,
+    compare: Option<fn(&C, &C) -> bool>,
+    changed: usize,
+}
+
+
+impl<C, R, Es> MargolusGrid<C, R, Es>
+    where C: Cell<State = Es> + Clone,
+          R: BlockRule<C>,
+          Es: BlockState,
+{
+    /// Constructs MargolusGrid with given ROWSxCOLS, block rule,
+    /// boundary policy and initial evolution state.
+    pub fn new(rows: u32, cols: u32, rule: R, boundary: Boundary<C>, state: C::State) -> Self {
+
+        let cells = (0..rows * cols)
+            .map(|offset| C::with_coord(GridCoord::from_offset(offset, rows, cols)))
+            .collect();
+
+        MargolusGrid {
+            cells: cells,
+            evolution_state: state,
+            rule: rule,
+            boundary: boundary,
+            dimensions: GridCoord::from_2d(cols as i32, rows as i32),
+            rows: rows,
+            cols: cols,
+            compare: None,
+            changed: 0,
+        }
+    }
+
+    /// Enables counting of cells changed by `update`
+    /// (see `Grid::changed`). It is off by default.
+    pub fn count_changes(&mut self)
+        where C: PartialEq,
+    {
+        self.compare = Some(differs::<C>);
+    }
+
+    fn update_block(&mut self, xs: (i32, i32), ys: (i32, i32)) {
+
+        let indices = [self.offset(xs.0, ys.0),
+                       self.offset(xs.1, ys.0),
+                       self.offset(xs.0, ys.1),
+                       self.offset(xs.1, ys.1)];
+
+        let mut block = [self.cells[indices[0]].clone(),
+                         self.cells[indices[1]].clone(),
+                         self.cells[indices[2]].clone(),
+                         self.cells[indices[3]].clone()];
+
+        self.rule.update(&mut block, &self.evolution_state);
+
+        for (cell, &index) in block.iter_mut().zip(indices.iter()) {
+            mem::swap(&mut self.cells[index], cell);
+
+            let coord = GridCoord::from_offset(index as u32, self.rows, self.cols);
+            self.cells[index].set_coord(&coord);
+
+            // Old version of cell is left in block after swap.
+            if self.compare.map_or(false, |differs| differs(&self.cells[index], cell)) {
+                self.changed += 1;
+            }
+        }
+    }
+
+    #[inline]
+    fn offset(&self, x: i32, y: i32) -> usize { (y * self.cols as i32 + x) as usize }
+}
+
+
+impl<C, R, Es> Grid for MargolusGrid<C, R, Es>
+    where C: Cell<State = Es> + Clone,
+          R: BlockRule<C>,
+          Es: BlockState,
+{
+    type Cell = C;
+    type Coord = GridCoord;
+
+    fn update(&mut self) {
+
+        let wrap = match self.boundary {
+            Boundary::Torus => true,
+            _ => false,
+        };
+
+        let start = (self.evolution_state.phase() % 2) as i32;
+        let cols = self.cols as i32;
+        let rows = self.rows as i32;
+
+        self.changed = 0;
+
+        let mut y = start;
+        while y < rows {
+            if let Some(ys) = span(y, rows, wrap) {
+
+                let mut x = start;
+                while x < cols {
+                    if let Some(xs) = span(x, cols, wrap) {
+                        self.update_block(xs, ys);
+                    }
+                    x += 2;
+                }
+            }
+            y += 2;
+        }
+
+        self.evolution_state.update();
+    }
+
+    fn set_cells(&mut self, new_cells: Vec<Self::Cell>) {
+
+        for cell in new_cells.into_iter() {
+
+            let index;
+
+            {
+                let coord = cell.coord();
+                index = self.offset(coord.x(), coord.y());
+            }
+
+            self.cells[index] = cell;
+        }
+    }
+
+    fn cells(&self) -> &[Self::Cell] { &self.cells }
+
+    fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { &self.evolution_state }
+
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) {
+        self.evolution_state = state;
+    }
+
+    fn size(&self) -> Self::Coord { self.dimensions.clone() }
+
+    fn changed(&self) -> Option<usize> { self.compare.map(|_| self.changed) }
+}
+
+
+/// Returns both positions of block which starts at `start`
+/// on axis of length `len`. Block which does not fit
+/// wraps around only when there is room for whole block.
+fn span(start: i32, len: i32, wrap: bool) -> Option<(i32, i32)> {
+    if start + 1 < len {
+        Some((start, start + 1))
+    } else if wrap && len % 2 == 0 {
+        Some((start, 0))
+    } else {
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use traits::Cell;
+    use traits::Neighbors;
+    use traits::Coord;
+    use traits::Grid;
+    use traits::EvolutionState;
+    use grid::Boundary;
+    use super::MargolusGrid;
+    use super::BlockRule;
+    use super::BlockState;
+    use super::span;
+
+
+    struct Phase(u32);
+
+    impl EvolutionState for Phase {
+        fn update(&mut self) { self.0 += 1; }
+    }
+
+    impl BlockState for Phase {
+        fn phase(&self) -> u32 { self.0 }
+    }
+
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Particle {
+        coord: (i32, i32),
+        full: bool,
+    }
+
+    impl Cell for Particle {
+        type Coord = (i32, i32);
+        type State = Phase;
+
+        fn update<'a, I>(&'a mut self, _: &'a Self, _: I, _: &Self::State)
+            where I: Neighbors<'a, Self>,
+        {
+            unreachable!()
+        }
+
+        fn with_coord<C: Coord>(coord: C) -> Self {
+            Particle {
+                coord: (coord.x(), coord.y()),
+                full: false,
+            }
+        }
+
+        fn coord(&self) -> &Self::Coord { &self.coord }
+
+        fn set_coord<C: Coord>(&mut self, coord: &C) { self.coord = (coord.x(), coord.y()); }
+    }
+
+
+    /// Rotates block clockwise.
+    struct Rotate;
+
+    impl BlockRule<Particle> for Rotate {
+        fn update(&self, block: &mut [Particle; 4], _: &Phase) {
+            // [tl, tr, bl, br] -> [bl, tl, br, tr]
+            block.swap(0, 1);
+            block.swap(0, 2);
+            block.swap(2, 3);
+        }
+    }
+
+
+    fn full_coords<G: Grid<Cell = Particle>>(grid: &G) -> Vec<(i32, i32)> {
+        grid.cells()
+            .iter()
+            .filter(|cell| cell.full)
+            .map(|cell| cell.coord)
+            .collect()
+    }
+
+    fn setup(boundary: Boundary<Particle>,
+             coord: (i32, i32))
+             -> MargolusGrid<Particle, Rotate, Phase> {
+        let mut grid = MargolusGrid::new(4, 4, Rotate, boundary, Phase(0));
+        grid.set_cells(vec![Particle {
+                                coord: coord,
+                                full: true,
+                            }]);
+        grid
+    }
+
+    #[test]
+    fn test_alternating_blocks() {
+        let mut grid = setup(Boundary::Open, (0, 0));
+        assert_eq!(grid.changed(), None);
+        grid.count_changes();
+
+        grid.update();
+        assert_eq!(full_coords(&grid), vec![(1, 0)]);
+        assert_eq!(grid.changed(), Some(2));
+
+        // Odd phase: (1, 0) is in block cut by upper edge.
+        grid.update();
+        assert_eq!(full_coords(&grid), vec![(1, 0)]);
+        assert_eq!(grid.changed(), Some(0));
+
+        grid.update();
+        assert_eq!(full_coords(&grid), vec![(1, 1)]);
+
+        // Odd phase: (1, 1) is top-left of block starting at (1, 1).
+        grid.update();
+        assert_eq!(full_coords(&grid), vec![(2, 1)]);
+
+        // Cells keep their coords after being moved by rule.
+        for (i, cell) in grid.cells().iter().enumerate() {
+            assert_eq!(cell.coord, ((i % 4) as i32, (i / 4) as i32));
+        }
+    }
+
+    #[test]
+    fn test_wrapped_blocks() {
+        let mut grid = setup(Boundary::Torus, (0, 0));
+        grid.update();
+        grid.update();
+
+        // Block of odd phase is [(1, 3), (2, 3), (1, 0), (2, 0)].
+        assert_eq!(full_coords(&grid), vec![(1, 3)]);
+    }
+
+    #[test]
+    fn test_span() {
+        assert_eq!(span(0, 4, false), Some((0, 1)));
+        assert_eq!(span(3, 4, false), None);
+        assert_eq!(span(3, 4, true), Some((3, 0)));
+        assert_eq!(span(2, 3, true), None);
+    }
+}
@@ -1,7 +1,26 @@
 //! Module contains implemented grids and neighorhoods.
 
+pub mod onedim;
 pub mod twodim;
+pub mod hex;
+pub mod triangular;
+pub mod threedim;
+pub mod sparse;
+pub mod margolus;
+pub mod hashlife;
+pub mod graph;
 pub mod nhood;
+pub mod pipeline;
+mod boundary;
+mod mode;
+mod buffer;
+mod iter;
+
+pub use self::boundary::Boundary;
+pub use self::mode::UpdateMode;
+pub use self::iter::Iter;
+pub use self::pipeline::Pipeline;
+pub use self::pipeline::Phase;
 
 
 use traits::EvolutionState;
@@ -9,6 +28,7 @@ use traits::EvolutionState;
 /// Dummy evolution state to be used with
 /// cellular automata where this concept is
 /// not applicable.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EmptyState;
 
 impl EvolutionState for EmptyState {
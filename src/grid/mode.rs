@@ -0,0 +1,24 @@
+//! Order in which grids update their cells.
+
+
+/// How cells are updated within one generation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum UpdateMode {
+    /// All cells are updated at once: every cell sees
+    /// neighbors as they were in previous generation.
+    Synchronous,
+    /// Cells are updated one at a time in order they
+    /// are stored. Cell sees neighbors already updated
+    /// in this generation with their new values.
+    Sweep,
+    /// Same as `Sweep`, but order is random permutation
+    /// of cells, new one every generation. Permutations
+    /// are generated from given seed, so evolution
+    /// can be reproduced.
+    RandomOrder(u64),
+}
+
+
+impl Default for UpdateMode {
+    fn default() -> Self { UpdateMode::Synchronous }
+}
@@ -0,0 +1,99 @@
+use std::marker::PhantomData;
+
+use traits::Nhood;
+use traits::Coord;
+
+
+/// Implements circular neighborhood: all cells with
+/// Euclidean distance to given coord not greater than
+/// radius `r`.
+/// - | - | 0 | - | -
+/// - | 1 | 2 | 3 | -
+/// 4 | 5 | x | 6 | 7
+/// - | 8 | 9 | 10| -
+/// - | - | 11| - | -
+/// x - given coord (`r` = 2). Neighbors are returned row by row
+/// from top to bottom, every row from left to right.
+pub struct CircularNhood<C: Coord> {
+    radius: i32,
+    count: usize,
+    phantom: PhantomData<C>,
+}
+
+
+impl<C: Coord> CircularNhood<C> {
+    /// Constructs neighborhood with given radius.
+    pub fn with_radius(radius: u32) -> Self {
+
+        let mut nhood = CircularNhood {
+            radius: radius as i32,
+            count: 0,
+            phantom: PhantomData,
+        };
+        nhood.count = nhood.offsets().len();
+
+        nhood
+    }
+
+    fn offsets(&self) -> Vec<(i32, i32)> {
+        let r = self.radius;
+        let mut offsets = Vec::new();
+
+        for dy in -r..r + 1 {
+            for dx in -r..r + 1 {
+                if (dx != 0 || dy != 0) && dx * dx + dy * dy <= r * r {
+                    offsets.push((dx, dy));
+                }
+            }
+        }
+
+        offsets
+    }
+}
+
+impl<C: Coord> Nhood for CircularNhood<C> {
+    type Coord = C;
+
+    fn neighbors(&self, coord: &Self::Coord) -> Vec<Self::Coord> {
+
+        let x = coord.x();
+        let y = coord.y();
+
+        self.offsets()
+            .into_iter()
+            .map(|(dx, dy)| C::from_2d(x + dx, y + dy))
+            .collect()
+    }
+
+    fn neighbors_count(&self) -> usize { self.count }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use traits::Nhood;
+    use super::CircularNhood;
+
+    #[test]
+    fn test_circular_nhood() {
+        let nhood = CircularNhood::with_radius(2);
+
+        let center = (2, 2);
+
+        let neighbors = nhood.neighbors(&center);
+        assert_eq!(neighbors.len(), 12);
+        assert_eq!(neighbors.len(), nhood.neighbors_count());
+
+        assert_eq!(neighbors[0], (2, 0));
+        assert_eq!(neighbors[1], (1, 1));
+        assert_eq!(neighbors[4], (0, 2));
+        assert_eq!(neighbors[11], (2, 4));
+
+        // Radius 1 is the same as Von Neumann neighborhood.
+        assert_eq!(CircularNhood::with_radius(1).neighbors(&center),
+                   vec![(2, 1), (1, 2), (3, 2), (2, 3)]);
+        // Wider circle includes cells which are not on axes.
+        assert_eq!(CircularNhood::<(i32, i32)>::with_radius(3).neighbors_count(), 28);
+    }
+}
@@ -0,0 +1,69 @@
+use std::marker::PhantomData;
+
+use traits::Nhood;
+use traits::Coord;
+
+
+/// Implements hexagonal neighborhood for axial coords
+/// (pointy-topped hexes).
+///   0   1
+/// 2   x   3
+///   4   5
+/// x - given coord. Neighbors is numbered in order they returned.
+pub struct HexNhood<C: Coord> {
+    phantom: PhantomData<C>,
+}
+
+
+impl<C: Coord> HexNhood<C> {
+    /// Just constructor.
+    pub fn new() -> Self { HexNhood { phantom: PhantomData } }
+}
+
+impl<C: Coord> Nhood for HexNhood<C> {
+    type Coord = C;
+
+    //   0   1
+    // 2   x   3
+    //   4   5
+    fn neighbors(&self, coord: &Self::Coord) -> Vec<Self::Coord> {
+
+        let q = coord.x();
+        let r = coord.y();
+
+        let neighbors_coords = vec![
+                   C::from_2d(q, r - 1),     C::from_2d(q + 1, r - 1),
+            C::from_2d(q - 1, r),    /* x */        C::from_2d(q + 1, r),
+                   C::from_2d(q - 1, r + 1), C::from_2d(q, r + 1),
+        ];
+
+        neighbors_coords
+    }
+
+    fn neighbors_count(&self) -> usize { 6 }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use traits::Nhood;
+    use super::HexNhood;
+
+    #[test]
+    fn test_hex_nhood() {
+        let nhood = HexNhood::new();
+
+        let center = (1, 1);
+
+        let neighbors = nhood.neighbors(&center);
+        assert_eq!(neighbors.len(), nhood.neighbors_count());
+
+        assert_eq!(neighbors[0], (1, 0));
+        assert_eq!(neighbors[1], (2, 0));
+        assert_eq!(neighbors[2], (0, 1));
+        assert_eq!(neighbors[3], (2, 1));
+        assert_eq!(neighbors[4], (0, 2));
+        assert_eq!(neighbors[5], (1, 2));
+    }
+}
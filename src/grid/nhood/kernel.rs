@@ -0,0 +1,274 @@
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+
+use traits::Nhood;
+use traits::Coord;
+
+
+/// Errors of building `KernelNhood`.
+#[derive(Debug, PartialEq)]
+pub enum KernelError {
+    /// Kernel has no non-zero weights.
+    Empty,
+    /// Rows of matrix have different lengths.
+    Ragged,
+    /// Matrix has even number of rows or columns,
+    /// so it has no center.
+    NoCenter,
+    /// Center of matrix has non-zero weight.
+    CenterWeight,
+    /// Radius or width of kernel is not positive.
+    NotPositive,
+}
+
+
+impl fmt::Display for KernelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.description()) }
+}
+
+
+impl Error for KernelError {
+    fn description(&self) -> &str {
+        match *self {
+            KernelError::Empty => "kernel has no non-zero weights",
+            KernelError::Ragged => "rows of matrix have different lengths",
+            KernelError::NoCenter => "matrix has even number of rows or columns",
+            KernelError::CenterWeight => "center of matrix has non-zero weight",
+            KernelError::NotPositive => "radius or width of kernel is not positive",
+        }
+    }
+}
+
+
+/// Implements neighborhood where every neighbor has weight.
+/// Used by continuous automata (Lenia, SmoothLife, etc.)
+/// which convolve neighbors with kernel. Neighbors with
+/// zero weight are dropped. Neighbors are returned row by
+/// row from top to bottom, every row from left to right.
+/// Grids pass weights to `Cell::update` together with
+/// neighbors, so cells apply kernel with
+/// `Neighbors::weighted_sum`.
+pub struct KernelNhood<C: Coord> {
+    offsets: Vec<(i32, i32)>,
+    weights: Vec<f64>,
+    phantom: PhantomData<C>,
+}
+
+
+impl<C: Coord> KernelNhood<C> {
+    /// Constructs ring of cells with distance to center
+    /// between `inner` and `outer` radii inclusive.
+    /// Every cell of ring has weight 1. Ring without
+    /// cells is an error.
+    pub fn ring(inner: f64, outer: f64) -> Result<Self, KernelError> {
+        KernelNhood::from_fn(outer, |distance| if distance >= inner { 1.0 } else { 0.0 })
+    }
+
+    /// Constructs Gaussian shell used by Lenia. Distance to
+    /// center is scaled to `radius`, so weight of cell is
+    /// `exp(-((d / radius - peak) / width)^2 / 2)`. Radius
+    /// and width should be positive.
+    pub fn gaussian_shell(radius: f64, peak: f64, width: f64) -> Result<Self, KernelError> {
+        if !(width > 0.0) {
+            return Err(KernelError::NotPositive);
+        }
+
+        KernelNhood::from_fn(radius, |distance| {
+            let shift = (distance / radius - peak) / width;
+            (-shift * shift / 2.0).exp()
+        })
+    }
+
+    /// Constructs kernel from matrix of weights. Matrix
+    /// should have odd number of rows and columns and
+    /// zero weight in the center.
+    pub fn from_matrix(matrix: Vec<Vec<f64>>) -> Result<Self, KernelError> {
+
+        let rows = matrix.len();
+        let cols = matrix.first().map_or(0, |row| row.len());
+
+        if matrix.iter().any(|row| row.len() != cols) {
+            return Err(KernelError::Ragged);
+        }
+        if rows % 2 == 0 || cols % 2 == 0 {
+            return Err(KernelError::NoCenter);
+        }
+
+        let center_x = (cols / 2) as i32;
+        let center_y = (rows / 2) as i32;
+
+        let mut kernel = KernelNhood::empty();
+
+        for (y, row) in matrix.iter().enumerate() {
+            for (x, &weight) in row.iter().enumerate() {
+                let offset = (x as i32 - center_x, y as i32 - center_y);
+
+                if weight == 0.0 {
+                    continue;
+                }
+                if offset == (0, 0) {
+                    return Err(KernelError::CenterWeight);
+                }
+
+                kernel.offsets.push(offset);
+                kernel.weights.push(weight);
+            }
+        }
+
+        if kernel.weights.is_empty() {
+            return Err(KernelError::Empty);
+        }
+
+        Ok(kernel)
+    }
+
+    /// Scales weights so their sum is 1.
+    pub fn normalized(mut self) -> Self {
+        let total = self.weights.iter().fold(0.0, |total, weight| total + weight);
+
+        if total != 0.0 {
+            for weight in &mut self.weights {
+                *weight /= total;
+            }
+        }
+
+        self
+    }
+
+    /// Returns weights in the same order as neighbors.
+    pub fn weights(&self) -> &[f64] { &self.weights }
+
+    fn empty() -> Self {
+        KernelNhood {
+            offsets: Vec::new(),
+            weights: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Weighs cells within `radius` by distance to center.
+    fn from_fn<F: Fn(f64) -> f64>(radius: f64, weight: F) -> Result<Self, KernelError> {
+
+        if !(radius > 0.0) {
+            return Err(KernelError::NotPositive);
+        }
+
+        let r = radius.floor() as i32;
+        let mut kernel = KernelNhood::empty();
+
+        for dy in -r..r + 1 {
+            for dx in -r..r + 1 {
+                let distance = ((dx * dx + dy * dy) as f64).sqrt();
+
+                if (dx == 0 && dy == 0) || distance > radius {
+                    continue;
+                }
+
+                let weight = weight(distance);
+                if weight != 0.0 {
+                    kernel.offsets.push((dx, dy));
+                    kernel.weights.push(weight);
+                }
+            }
+        }
+
+        if kernel.weights.is_empty() {
+            return Err(KernelError::Empty);
+        }
+
+        Ok(kernel)
+    }
+}
+
+impl<C: Coord> Nhood for KernelNhood<C> {
+    type Coord = C;
+
+    fn neighbors(&self, coord: &Self::Coord) -> Vec<Self::Coord> {
+
+        let x = coord.x();
+        let y = coord.y();
+
+        self.offsets
+            .iter()
+            .map(|&(dx, dy)| C::from_2d(x + dx, y + dy))
+            .collect()
+    }
+
+    fn neighbors_count(&self) -> usize { self.offsets.len() }
+
+    fn weights(&self, _: &Self::Coord) -> Vec<f64> { self.weights.clone() }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use traits::Nhood;
+    use super::KernelError;
+    use super::KernelNhood;
+
+    type Kernel = KernelNhood<(i32, i32)>;
+
+    #[test]
+    fn test_ring() {
+        let kernel = Kernel::ring(1.5, 2.0).unwrap();
+
+        // Cells at distance 1 and 1.41 are left out.
+        assert_eq!(kernel.neighbors_count(), 4);
+        assert_eq!(kernel.neighbors(&(0, 0))[0], (0, -2));
+        assert_eq!(kernel.weights(), &[1.0; 4]);
+
+        assert_eq!(Kernel::ring(1.0, 2.0).unwrap().neighbors_count(), 12);
+
+        assert_eq!(Kernel::ring(2.0, 1.0).err(), Some(KernelError::Empty));
+        assert_eq!(Kernel::ring(0.0, 0.0).err(), Some(KernelError::NotPositive));
+    }
+
+    #[test]
+    fn test_gaussian_shell() {
+        let kernel = Kernel::gaussian_shell(2.0, 0.5, 0.15).unwrap().normalized();
+
+        assert_eq!(kernel.neighbors_count(), 12);
+
+        let total = kernel.weights().iter().fold(0.0, |total, weight| total + weight);
+        assert!((total - 1.0).abs() < 1e-9);
+
+        // Cells at distance 1 are on the peak of shell,
+        // so they outweigh cells at distance 2.
+        let neighbors = kernel.neighbors(&(0, 0));
+        let near = neighbors.iter().position(|&n| n == (0, -1)).unwrap();
+        let far = neighbors.iter().position(|&n| n == (0, -2)).unwrap();
+        assert!(kernel.weights()[near] > kernel.weights()[far]);
+
+        assert_eq!(Kernel::gaussian_shell(0.0, 0.5, 0.15).err(),
+                   Some(KernelError::NotPositive));
+        assert_eq!(Kernel::gaussian_shell(2.0, 0.5, 0.0).err(),
+                   Some(KernelError::NotPositive));
+    }
+
+    #[test]
+    fn test_from_matrix() {
+        let kernel = Kernel::from_matrix(vec![vec![0.0, 1.0, 0.0],
+                                              vec![2.0, 0.0, 2.0],
+                                              vec![0.0, 1.0, 0.0]])
+            .unwrap();
+
+        assert_eq!(kernel.neighbors(&(1, 1)), vec![(1, 0), (0, 1), (2, 1), (1, 2)]);
+        assert_eq!(kernel.weights(), &[1.0, 2.0, 2.0, 1.0]);
+        assert_eq!(Nhood::weights(&kernel, &(1, 1)), vec![1.0, 2.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn test_invalid_matrix() {
+        assert_eq!(Kernel::from_matrix(vec![]).err(), Some(KernelError::NoCenter));
+        assert_eq!(Kernel::from_matrix(vec![vec![0.0; 3]; 3]).err(),
+                   Some(KernelError::Empty));
+        assert_eq!(Kernel::from_matrix(vec![vec![1.0; 3]; 3]).err(),
+                   Some(KernelError::CenterWeight));
+        assert_eq!(Kernel::from_matrix(vec![vec![1.0; 3], vec![1.0; 2], vec![1.0; 3]]).err(),
+                   Some(KernelError::Ragged));
+        assert_eq!(Kernel::from_matrix(vec![vec![1.0; 2]; 3]).err(),
+                   Some(KernelError::NoCenter));
+    }
+}
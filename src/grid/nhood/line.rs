@@ -0,0 +1,63 @@
+use std::marker::PhantomData;
+
+use traits::Nhood;
+use traits::Coord;
+
+
+/// Implements one-dimensional neighborhood of radius `r`:
+/// `r` cells to the left and `r` cells to the right.
+/// 0 | 1 | x | 2 | 3
+/// x - given coord (`r` = 2). Neighbors is numbered in order they returned.
+pub struct LineNhood<C: Coord> {
+    radius: u32,
+    phantom: PhantomData<C>,
+}
+
+
+impl<C: Coord> LineNhood<C> {
+    /// Constructs neighborhood with given radius.
+    pub fn new(radius: u32) -> Self {
+        LineNhood {
+            radius: radius,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<C: Coord> Nhood for LineNhood<C> {
+    type Coord = C;
+
+    fn neighbors(&self, coord: &Self::Coord) -> Vec<Self::Coord> {
+
+        let x = coord.x();
+        let y = coord.y();
+        let r = self.radius as i32;
+
+        (-r..r + 1)
+            .filter(|&dx| dx != 0)
+            .map(|dx| C::from_2d(x + dx, y))
+            .collect()
+    }
+
+    fn neighbors_count(&self) -> usize { 2 * self.radius as usize }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use traits::Nhood;
+    use super::LineNhood;
+
+    #[test]
+    fn test_line_nhood() {
+        let nhood = LineNhood::new(2);
+
+        let center = (2, 0);
+
+        let neighbors = nhood.neighbors(&center);
+        assert_eq!(neighbors.len(), nhood.neighbors_count());
+
+        assert_eq!(neighbors, vec![(0, 0), (1, 0), (3, 0), (4, 0)]);
+    }
+}
@@ -0,0 +1,212 @@
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+
+use traits::Nhood;
+use traits::Coord;
+
+
+/// Errors of building `MaskNhood`.
+#[derive(Debug, PartialEq)]
+pub enum MaskError {
+    /// Mask has no neighbors at all.
+    Empty,
+    /// Stencil has no center.
+    NoCenter,
+    /// Stencil has more than one center.
+    ManyCenters,
+    /// Stencil has unknown symbol at given row and column.
+    UnknownSymbol(char, usize, usize),
+    /// Offset `(0, 0)` is a cell itself, not its neighbor.
+    CenterOffset,
+    /// Offset is given more than once.
+    DuplicateOffset(i32, i32),
+}
+
+
+impl fmt::Display for MaskError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MaskError::UnknownSymbol(symbol, row, col) => {
+                write!(f, "unknown symbol {:?} at row {}, column {}", symbol, row, col)
+            },
+            MaskError::DuplicateOffset(dx, dy) => write!(f, "duplicate offset ({}, {})", dx, dy),
+            _ => write!(f, "{}", self.description()),
+        }
+    }
+}
+
+
+impl Error for MaskError {
+    fn description(&self) -> &str {
+        match *self {
+            MaskError::Empty => "mask has no neighbors",
+            MaskError::NoCenter => "stencil has no center",
+            MaskError::ManyCenters => "stencil has more than one center",
+            MaskError::UnknownSymbol(..) => "stencil has unknown symbol",
+            MaskError::CenterOffset => "offset (0, 0) is not a neighbor",
+            MaskError::DuplicateOffset(..) => "offset is given more than once",
+        }
+    }
+}
+
+
+/// Implements neighborhood of arbitrary shape given
+/// by stencil or by list of offsets relative to cell.
+pub struct MaskNhood<C: Coord> {
+    offsets: Vec<(i32, i32)>,
+    phantom: PhantomData<C>,
+}
+
+
+impl<C: Coord> MaskNhood<C> {
+    /// Builds neighborhood from ASCII stencil. `x` marks the
+    /// cell itself, `#` marks neighbor and `.` marks cell which
+    /// is not a neighbor. Leading and trailing whitespace of
+    /// every line and empty lines are ignored. Neighbors are
+    /// returned row by row from top to bottom, every row from
+    /// left to right.
+    ///
+    /// ```
+    /// use celly::grid::nhood::MaskNhood;
+    /// use celly::traits::Nhood;
+    ///
+    /// let nhood = MaskNhood::<(i32, i32)>::from_stencil("#.#\n.x.\n#.#").unwrap();
+    /// assert_eq!(nhood.neighbors_count(), 4);
+    /// ```
+    pub fn from_stencil(stencil: &str) -> Result<Self, MaskError> {
+
+        let mut center = None;
+        let mut marked = Vec::new();
+
+        let lines = stencil.lines().map(|line| line.trim()).filter(|line| !line.is_empty());
+
+        for (row, line) in lines.enumerate() {
+            for (col, symbol) in line.chars().enumerate() {
+                match symbol {
+                    '#' => marked.push((col as i32, row as i32)),
+                    '.' => {},
+                    'x' => {
+                        if center.is_some() {
+                            return Err(MaskError::ManyCenters);
+                        }
+                        center = Some((col as i32, row as i32));
+                    },
+                    _ => return Err(MaskError::UnknownSymbol(symbol, row, col)),
+                }
+            }
+        }
+
+        let (cx, cy) = try!(center.ok_or(MaskError::NoCenter));
+        let offsets = marked.into_iter().map(|(x, y)| (x - cx, y - cy)).collect();
+
+        MaskNhood::from_offsets(offsets)
+    }
+
+    /// Builds neighborhood from list of offsets. Neighbors
+    /// are returned in the same order.
+    pub fn from_offsets(offsets: Vec<(i32, i32)>) -> Result<Self, MaskError> {
+
+        if offsets.is_empty() {
+            return Err(MaskError::Empty);
+        }
+
+        for (i, &offset) in offsets.iter().enumerate() {
+            if offset == (0, 0) {
+                return Err(MaskError::CenterOffset);
+            }
+            if offsets[..i].contains(&offset) {
+                return Err(MaskError::DuplicateOffset(offset.0, offset.1));
+            }
+        }
+
+        Ok(MaskNhood {
+            offsets: offsets,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Returns offsets of neighbors relative to cell.
+    pub fn offsets(&self) -> &[(i32, i32)] { &self.offsets }
+}
+
+impl<C: Coord> Nhood for MaskNhood<C> {
+    type Coord = C;
+
+    fn neighbors(&self, coord: &Self::Coord) -> Vec<Self::Coord> {
+
+        let x = coord.x();
+        let y = coord.y();
+
+        self.offsets
+            .iter()
+            .map(|&(dx, dy)| C::from_2d(x + dx, y + dy))
+            .collect()
+    }
+
+    fn neighbors_count(&self) -> usize { self.offsets.len() }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use traits::Nhood;
+    use super::MaskError;
+    use super::MaskNhood;
+
+    #[test]
+    fn test_from_stencil() {
+        let nhood = MaskNhood::from_stencil("
+            #.#
+            .x.
+            #.#
+        ")
+            .unwrap();
+
+        let neighbors = nhood.neighbors(&(5, 5));
+        assert_eq!(neighbors.len(), nhood.neighbors_count());
+        assert_eq!(neighbors, vec![(4, 4), (6, 4), (4, 6), (6, 6)]);
+    }
+
+    #[test]
+    fn test_knight_move() {
+        let stencil = ".#.#.\n#...#\n..x..\n#...#\n.#.#.";
+        let nhood: MaskNhood<(i32, i32)> = MaskNhood::from_stencil(stencil).unwrap();
+
+        assert_eq!(nhood.neighbors_count(), 8);
+        assert_eq!(nhood.offsets()[0], (-1, -2));
+        assert_eq!(nhood.offsets()[7], (1, 2));
+    }
+
+    #[test]
+    fn test_asymmetric_stencil() {
+        let nhood: MaskNhood<(i32, i32)> = MaskNhood::from_stencil("x##").unwrap();
+        assert_eq!(nhood.offsets(), &[(1, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn test_invalid_stencil() {
+        type Mask = MaskNhood<(i32, i32)>;
+
+        assert_eq!(Mask::from_stencil("#.#").err(), Some(MaskError::NoCenter));
+        assert_eq!(Mask::from_stencil("x#x").err(), Some(MaskError::ManyCenters));
+        assert_eq!(Mask::from_stencil("...\n.x.").err(), Some(MaskError::Empty));
+        assert_eq!(Mask::from_stencil("#.#\n.x?").err(),
+                   Some(MaskError::UnknownSymbol('?', 1, 2)));
+    }
+
+    #[test]
+    fn test_from_offsets() {
+        let nhood: MaskNhood<(i32, i32)> = MaskNhood::from_offsets(vec![(0, -1), (2, 3)]).unwrap();
+        assert_eq!(nhood.neighbors(&(1, 1)), vec![(1, 0), (3, 4)]);
+
+        type Mask = MaskNhood<(i32, i32)>;
+
+        assert_eq!(Mask::from_offsets(vec![]).err(), Some(MaskError::Empty));
+        assert_eq!(Mask::from_offsets(vec![(1, 0), (0, 0)]).err(),
+                   Some(MaskError::CenterOffset));
+        assert_eq!(Mask::from_offsets(vec![(1, 0), (0, 1), (1, 0)]).err(),
+                   Some(MaskError::DuplicateOffset(1, 0)));
+    }
+}
@@ -2,6 +2,25 @@
 
 mod moore;
 mod von_neumann;
+mod circular;
+mod hex;
+mod moore3;
+mod von_neumann3;
+mod line;
+mod triangular;
+mod mask;
+mod kernel;
 
 pub use self::moore::MooreNhood;
 pub use self::von_neumann::VonNeumannNhood;
+pub use self::circular::CircularNhood;
+pub use self::hex::HexNhood;
+pub use self::moore3::MooreNhood3;
+pub use self::von_neumann3::VonNeumannNhood3;
+pub use self::line::LineNhood;
+pub use self::triangular::TriEdgeNhood;
+pub use self::triangular::TriVertexNhood;
+pub use self::mask::MaskNhood;
+pub use self::mask::MaskError;
+pub use self::kernel::KernelNhood;
+pub use self::kernel::KernelError;
@@ -4,19 +4,31 @@ use traits::Nhood;
 use traits::Coord;
 
 
-/// Implements Moore neighborhood.
+/// Implements Moore neighborhood: all cells of square
+/// with side `2r + 1` around given coord (`r` - radius).
 /// 0 | 1 | 2
 /// 3 | x | 4
 /// 5 | 6 | 7
-/// x - given coord. Neighbors is numbered in order they returned.
+/// x - given coord (`r` = 1). Neighbors is numbered in order they returned.
+/// For any radius neighbors are returned row by row from top to
+/// bottom, every row from left to right.
 pub struct MooreNhood<C: Coord> {
+    radius: i32,
     phantom: PhantomData<C>,
 }
 
 
 impl<C: Coord> MooreNhood<C> {
-    /// Just constructor.
-    pub fn new() -> Self { MooreNhood { phantom: PhantomData } }
+    /// Just constructor. Radius is 1.
+    pub fn new() -> Self { MooreNhood::with_radius(1) }
+
+    /// Constructs neighborhood with given radius.
+    pub fn with_radius(radius: u32) -> Self {
+        MooreNhood {
+            radius: radius as i32,
+            phantom: PhantomData,
+        }
+    }
 }
 
 impl<C: Coord> Nhood for MooreNhood<C> {
@@ -29,21 +41,25 @@ impl<C: Coord> Nhood for MooreNhood<C> {
 
         let x = coord.x();
         let y = coord.y();
+        let r = self.radius;
+
+        let mut neighbors_coords = Vec::with_capacity(self.neighbors_count());
 
-        let neighbors_coords = vec![C::from_2d(x - 1, y - 1),
-                                    C::from_2d(x, y - 1),
-                                    C::from_2d(x + 1, y - 1),
-                                    C::from_2d(x - 1, y),
-                                    // x
-                                    C::from_2d(x + 1, y),
-                                    C::from_2d(x - 1, y + 1),
-                                    C::from_2d(x, y + 1),
-                                    C::from_2d(x + 1, y + 1)];
+        for dy in -r..r + 1 {
+            for dx in -r..r + 1 {
+                if dx != 0 || dy != 0 {
+                    neighbors_coords.push(C::from_2d(x + dx, y + dy));
+                }
+            }
+        }
 
         neighbors_coords
     }
 
-    fn neighbors_count(&self) -> usize { 8 }
+    fn neighbors_count(&self) -> usize {
+        let side = (2 * self.radius + 1) as usize;
+        side * side - 1
+    }
 }
 
 #[cfg(test)]
@@ -70,4 +86,22 @@ mod tests {
         assert_eq!(neighbors[6], (1, 2));
         assert_eq!(neighbors[7], (2, 2));
     }
+
+    #[test]
+    fn test_moore_nhood_with_radius() {
+        let nhood = MooreNhood::with_radius(2);
+
+        let center = (2, 2);
+
+        let neighbors = nhood.neighbors(&center);
+        assert_eq!(neighbors.len(), 24);
+        assert_eq!(neighbors.len(), nhood.neighbors_count());
+
+        assert_eq!(neighbors[0], (0, 0));
+        assert_eq!(neighbors[4], (4, 0));
+        assert_eq!(neighbors[11], (1, 2));
+        assert_eq!(neighbors[12], (3, 2));
+        assert_eq!(neighbors[23], (4, 4));
+        assert!(!neighbors.contains(&center));
+    }
 }
@@ -0,0 +1,71 @@
+use std::marker::PhantomData;
+
+use traits::Nhood;
+use traits::Coord;
+
+
+/// Implements 3D Moore neighborhood: all 26 cells
+/// of 3x3x3 cube around given coord. Neighbors are
+/// returned layer by layer (from `z - 1` to `z + 1`),
+/// each layer is ordered as in `MooreNhood`
+/// (center of middle layer is skipped).
+pub struct MooreNhood3<C: Coord> {
+    phantom: PhantomData<C>,
+}
+
+
+impl<C: Coord> MooreNhood3<C> {
+    /// Just constructor.
+    pub fn new() -> Self { MooreNhood3 { phantom: PhantomData } }
+}
+
+impl<C: Coord> Nhood for MooreNhood3<C> {
+    type Coord = C;
+
+    fn neighbors(&self, coord: &Self::Coord) -> Vec<Self::Coord> {
+
+        let x = coord.x();
+        let y = coord.y();
+        let z = coord.z();
+
+        let mut neighbors_coords = Vec::with_capacity(self.neighbors_count());
+
+        for dz in -1..2 {
+            for dy in -1..2 {
+                for dx in -1..2 {
+                    if dx != 0 || dy != 0 || dz != 0 {
+                        neighbors_coords.push(C::from_3d(x + dx, y + dy, z + dz));
+                    }
+                }
+            }
+        }
+
+        neighbors_coords
+    }
+
+    fn neighbors_count(&self) -> usize { 26 }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use traits::Nhood;
+    use super::MooreNhood3;
+
+    #[test]
+    fn test_moore_nhood3() {
+        let nhood = MooreNhood3::new();
+
+        let center = (1, 1, 1);
+
+        let neighbors = nhood.neighbors(&center);
+        assert_eq!(neighbors.len(), nhood.neighbors_count());
+
+        assert_eq!(neighbors[0], (0, 0, 0));
+        assert_eq!(neighbors[8], (2, 2, 0));
+        assert_eq!(neighbors[12], (0, 1, 1));
+        assert_eq!(neighbors[13], (2, 1, 1));
+        assert_eq!(neighbors[25], (2, 2, 2));
+        assert!(!neighbors.contains(&center));
+    }
+}
@@ -0,0 +1,138 @@
+use std::marker::PhantomData;
+
+use traits::Nhood;
+use traits::Coord;
+use grid::triangular::Orientation;
+
+
+/// Implements neighborhood of triangles sharing an edge
+/// with given one. Orientation of triangle is taken
+/// from its coord (see `Orientation::of`).
+/// Up:            Down:
+/// 0 | x | 1      - | 0 | -
+/// - | 2 | -      1 | x | 2
+/// x - given coord. Neighbors is numbered in order they returned.
+pub struct TriEdgeNhood<C: Coord> {
+    phantom: PhantomData<C>,
+}
+
+
+impl<C: Coord> TriEdgeNhood<C> {
+    /// Just constructor.
+    pub fn new() -> Self { TriEdgeNhood { phantom: PhantomData } }
+}
+
+impl<C: Coord> Nhood for TriEdgeNhood<C> {
+    type Coord = C;
+
+    fn neighbors(&self, coord: &Self::Coord) -> Vec<Self::Coord> {
+
+        let x = coord.x();
+        let y = coord.y();
+
+        match Orientation::of(coord) {
+            Orientation::Up => {
+                vec![C::from_2d(x - 1, y), C::from_2d(x + 1, y), C::from_2d(x, y + 1)]
+            },
+            Orientation::Down => {
+                vec![C::from_2d(x, y - 1), C::from_2d(x - 1, y), C::from_2d(x + 1, y)]
+            },
+        }
+    }
+
+    fn neighbors_count(&self) -> usize { 3 }
+}
+
+
+/// Implements neighborhood of triangles sharing a vertex
+/// with given one. Neighbors are returned row by row.
+/// Up:                       Down:
+/// - | 0 | 1 | 2 | -         0 | 1 | 2 | 3 | 4
+/// 3 | 4 | x | 5 | 6         5 | 6 | x | 7 | 8
+/// 7 | 8 | 9 | 10| 11        - | 9 | 10| 11| -
+/// x - given coord. Neighbors is numbered in order they returned.
+pub struct TriVertexNhood<C: Coord> {
+    phantom: PhantomData<C>,
+}
+
+
+impl<C: Coord> TriVertexNhood<C> {
+    /// Just constructor.
+    pub fn new() -> Self { TriVertexNhood { phantom: PhantomData } }
+}
+
+impl<C: Coord> Nhood for TriVertexNhood<C> {
+    type Coord = C;
+
+    fn neighbors(&self, coord: &Self::Coord) -> Vec<Self::Coord> {
+
+        let x = coord.x();
+        let y = coord.y();
+
+        // Half-widths of upper and lower rows.
+        let (above, below) = match Orientation::of(coord) {
+            Orientation::Up => (1, 2),
+            Orientation::Down => (2, 1),
+        };
+
+        let mut neighbors_coords = Vec::with_capacity(self.neighbors_count());
+
+        for dx in -above..above + 1 {
+            neighbors_coords.push(C::from_2d(x + dx, y - 1));
+        }
+        for dx in -2..3 {
+            if dx != 0 {
+                neighbors_coords.push(C::from_2d(x + dx, y));
+            }
+        }
+        for dx in -below..below + 1 {
+            neighbors_coords.push(C::from_2d(x + dx, y + 1));
+        }
+
+        neighbors_coords
+    }
+
+    fn neighbors_count(&self) -> usize { 12 }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use traits::Nhood;
+    use super::TriEdgeNhood;
+    use super::TriVertexNhood;
+
+    #[test]
+    fn test_edge_nhood() {
+        let nhood = TriEdgeNhood::new();
+
+        let up = (2, 2);
+        let neighbors = nhood.neighbors(&up);
+        assert_eq!(neighbors.len(), nhood.neighbors_count());
+        assert_eq!(neighbors, vec![(1, 2), (3, 2), (2, 3)]);
+
+        let down = (3, 2);
+        let neighbors = nhood.neighbors(&down);
+        assert_eq!(neighbors.len(), nhood.neighbors_count());
+        assert_eq!(neighbors, vec![(3, 1), (2, 2), (4, 2)]);
+    }
+
+    #[test]
+    fn test_vertex_nhood() {
+        let nhood = TriVertexNhood::new();
+
+        let up = (2, 2);
+        let neighbors = nhood.neighbors(&up);
+        assert_eq!(neighbors.len(), nhood.neighbors_count());
+        assert_eq!(&neighbors[..3], &[(1, 1), (2, 1), (3, 1)]);
+        assert_eq!(&neighbors[3..7], &[(0, 2), (1, 2), (3, 2), (4, 2)]);
+        assert_eq!(&neighbors[7..], &[(0, 3), (1, 3), (2, 3), (3, 3), (4, 3)]);
+
+        let down = (3, 2);
+        let neighbors = nhood.neighbors(&down);
+        assert_eq!(neighbors.len(), nhood.neighbors_count());
+        assert_eq!(&neighbors[..5], &[(1, 1), (2, 1), (3, 1), (4, 1), (5, 1)]);
+        assert_eq!(&neighbors[9..], &[(2, 3), (3, 3), (4, 3)]);
+    }
+}
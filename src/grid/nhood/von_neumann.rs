@@ -4,19 +4,32 @@ use traits::Nhood;
 use traits::Coord;
 
 
-/// Implements Von Neumann neighborhood.
+/// Implements Von Neumann neighborhood: all cells
+/// with Manhattan distance to given coord not greater
+/// than radius `r`.
 /// - | 0 | -
 /// 1 | x | 2
 /// - | 3 | -
-/// x - given coord. Neighbors is number in order they returned.
+/// x - given coord (`r` = 1). Neighbors is number in order they returned.
+/// For any radius neighbors are returned row by row from top to
+/// bottom, every row from left to right.
 pub struct VonNeumannNhood<C: Coord> {
+    radius: i32,
     phantom: PhantomData<C>,
 }
 
 
 impl<C: Coord> VonNeumannNhood<C> {
-    /// Just constructor.
-    pub fn new() -> Self { VonNeumannNhood { phantom: PhantomData } }
+    /// Just constructor. Radius is 1.
+    pub fn new() -> Self { VonNeumannNhood::with_radius(1) }
+
+    /// Constructs neighborhood with given radius.
+    pub fn with_radius(radius: u32) -> Self {
+        VonNeumannNhood {
+            radius: radius as i32,
+            phantom: PhantomData,
+        }
+    }
 }
 
 impl<C: Coord> Nhood for VonNeumannNhood<C> {
@@ -29,17 +42,26 @@ impl<C: Coord> Nhood for VonNeumannNhood<C> {
 
         let x = coord.x();
         let y = coord.y();
+        let r = self.radius;
+
+        let mut neighbors_coords = Vec::with_capacity(self.neighbors_count());
 
-        let neighbors_coords = vec![
-                                      C::from_2d(x, y - 1),
-            C::from_2d(x - 1, y),     /* x */               C::from_2d(x + 1, y),
-                                      C::from_2d(x, y + 1),
-        ];
+        for dy in -r..r + 1 {
+            let half_width = r - dy.abs();
+            for dx in -half_width..half_width + 1 {
+                if dx != 0 || dy != 0 {
+                    neighbors_coords.push(C::from_2d(x + dx, y + dy));
+                }
+            }
+        }
 
         neighbors_coords
     }
 
-    fn neighbors_count(&self) -> usize { 4 }
+    fn neighbors_count(&self) -> usize {
+        let r = self.radius as usize;
+        2 * r * (r + 1)
+    }
 }
 
 
@@ -63,4 +85,22 @@ mod tests {
         assert_eq!(neighbors[2], (2, 1));
         assert_eq!(neighbors[3], (1, 2));
     }
+
+    #[test]
+    fn test_von_neumann_nhood_with_radius() {
+        let nhood = VonNeumannNhood::with_radius(2);
+
+        let center = (2, 2);
+
+        let neighbors = nhood.neighbors(&center);
+        assert_eq!(neighbors.len(), 12);
+        assert_eq!(neighbors.len(), nhood.neighbors_count());
+
+        assert_eq!(neighbors[0], (2, 0));
+        assert_eq!(neighbors[1], (1, 1));
+        assert_eq!(neighbors[3], (3, 1));
+        assert_eq!(neighbors[4], (0, 2));
+        assert_eq!(neighbors[7], (4, 2));
+        assert_eq!(neighbors[11], (2, 4));
+    }
 }
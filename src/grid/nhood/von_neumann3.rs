@@ -0,0 +1,67 @@
+use std::marker::PhantomData;
+
+use traits::Nhood;
+use traits::Coord;
+
+
+/// Implements 3D Von Neumann neighborhood: cells
+/// sharing a face with given coord.
+/// Neighbors are returned in the following order:
+/// 0 - `z - 1`, 1..4 - as in `VonNeumannNhood`, 5 - `z + 1`.
+pub struct VonNeumannNhood3<C: Coord> {
+    phantom: PhantomData<C>,
+}
+
+
+impl<C: Coord> VonNeumannNhood3<C> {
+    /// Just constructor.
+    pub fn new() -> Self { VonNeumannNhood3 { phantom: PhantomData } }
+}
+
+impl<C: Coord> Nhood for VonNeumannNhood3<C> {
+    type Coord = C;
+
+    fn neighbors(&self, coord: &Self::Coord) -> Vec<Self::Coord> {
+
+        let x = coord.x();
+        let y = coord.y();
+        let z = coord.z();
+
+        let neighbors_coords = vec![
+            C::from_3d(x, y, z - 1),
+                                          C::from_3d(x, y - 1, z),
+            C::from_3d(x - 1, y, z),      /* x */                      C::from_3d(x + 1, y, z),
+                                          C::from_3d(x, y + 1, z),
+            C::from_3d(x, y, z + 1),
+        ];
+
+        neighbors_coords
+    }
+
+    fn neighbors_count(&self) -> usize { 6 }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use traits::Nhood;
+    use super::VonNeumannNhood3;
+
+    #[test]
+    fn test_von_neumann_nhood3() {
+        let nhood = VonNeumannNhood3::new();
+
+        let center = (1, 1, 1);
+
+        let neighbors = nhood.neighbors(&center);
+        assert_eq!(neighbors.len(), nhood.neighbors_count());
+
+        assert_eq!(neighbors[0], (1, 1, 0));
+        assert_eq!(neighbors[1], (1, 0, 1));
+        assert_eq!(neighbors[2], (0, 1, 1));
+        assert_eq!(neighbors[3], (2, 1, 1));
+        assert_eq!(neighbors[4], (1, 2, 1));
+        assert_eq!(neighbors[5], (1, 1, 2));
+    }
+}
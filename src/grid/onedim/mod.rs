@@ -0,0 +1,127 @@
+//! 1D grid for elementary and totalistic automata.
+
+mod space_time;
+mod test;
+
+use traits::Grid;
+use traits::Cell;
+use traits::EvolutionState;
+use traits::Nhood;
+use traits::Coord;
+use grid::Boundary;
+use grid::twodim::GridCoord;
+
+use super::buffer::DoubleBuffer;
+pub use self::space_time::SpaceTime;
+
+/// 1D grid. Cells are placed in one row, so they
+/// have `GridCoord` with `y` equal to 0.
+/// Update process is the same as in `TwodimGrid`.
+/// `Klein` boundary is the same as `Torus` here.
+pub struct OnedimGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = GridCoord>,
+          Es: EvolutionState,
+{
+    buffer: DoubleBuffer<C>,
+    evolution_state: Es,
+    nhood: N,
+    boundary: Boundary<C>,
+    dimensions: GridCoord,
+    cols: u32,
+}
+
+
+impl<C, N, Es> OnedimGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = GridCoord>,
+          Es: EvolutionState,
+{
+    /// Constructs OnedimGrid with given COLS, neighborhood
+    /// strategy, boundary policy, initial evolution state, threads count.
+    pub fn new(cols: u32, nhood: N, boundary: Boundary<C>, state: C::State, threads: u32) -> Self {
+
+        let mut grid = OnedimGrid {
+            buffer: DoubleBuffer::new(cols as usize, threads),
+            evolution_state: state,
+            nhood: nhood,
+            boundary: boundary,
+            cols: cols,
+            dimensions: GridCoord::from_2d(cols as i32, 1),
+        };
+
+        grid.init();
+
+        grid
+    }
+
+    /// Enables counting of changed cells (see `Grid::changed`).
+    pub fn count_changes(&mut self)
+        where C: PartialEq,
+    {
+        self.buffer.count_changes();
+    }
+
+    fn init(&mut self) {
+
+        for offset in 0..self.cols {
+
+            let coord = GridCoord::from_offset(offset, 1, self.cols);
+
+            let neighbors = self.get_neighbors(&coord);
+            let offsets = self.nhood.offsets(&coord);
+            let weights = self.nhood.weights(&coord);
+            let cell = C::with_coord(coord);
+
+            self.buffer.push(cell, neighbors, offsets, weights);
+        }
+
+        self.buffer.split();
+    }
+
+    fn get_neighbors(&self, coord: &GridCoord) -> Vec<Option<usize>> {
+
+        let cols = self.cols as i32;
+
+        self.nhood
+            .neighbors(coord)
+            .iter()
+            .map(|coord| self.boundary.resolve(coord.x(), 0, cols, 1).map(|(x, _)| x as usize))
+            .collect()
+    }
+}
+
+
+impl<C, N, Es> Grid for OnedimGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = GridCoord>,
+          Es: EvolutionState + Send,
+{
+    type Cell = C;
+    type Coord = GridCoord;
+
+    fn update(&mut self) {
+        self.buffer.update(self.boundary.ghost(), &self.evolution_state);
+        self.evolution_state.update();
+    }
+
+    fn set_cells(&mut self, new_cells: Vec<Self::Cell>) {
+
+        for cell in new_cells.into_iter() {
+            let index = cell.coord().x() as usize;
+            self.buffer.cells[index] = cell;
+        }
+    }
+
+    fn cells(&self) -> &[Self::Cell] { &self.buffer.cells }
+
+    fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { &self.evolution_state }
+
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) {
+        self.evolution_state = state;
+    }
+
+    fn size(&self) -> Self::Coord { self.dimensions.clone() }
+
+    fn changed(&self) -> Option<usize> { self.buffer.changed() }
+}
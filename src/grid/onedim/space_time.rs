@@ -0,0 +1,125 @@
+use traits::Grid;
+use traits::Cell;
+use traits::Coord;
+use grid::twodim::GridCoord;
+
+
+/// Records every generation of 1D grid as a row of
+/// space-time diagram. Diagram is grid itself: `cells`
+/// returns all recorded rows (cell's `y` is its generation)
+/// and `size` returns count of columns and recorded rows.
+/// Updates and external changes are passed to wrapped grid.
+pub struct SpaceTime<G: Grid> {
+    grid: G,
+    history: Vec<G::Cell>,
+    cols: i32,
+    generations: i32,
+}
+
+
+impl<G> SpaceTime<G>
+    where G: Grid,
+          G::Cell: Clone,
+{
+    /// Wraps given grid. Its current state becomes
+    /// the first row of diagram.
+    pub fn new(grid: G) -> Self {
+
+        let cols = grid.cells().len() as i32;
+
+        let mut space_time = SpaceTime {
+            grid: grid,
+            history: Vec::new(),
+            cols: cols,
+            generations: 0,
+        };
+
+        space_time.record();
+
+        space_time
+    }
+
+    /// Getter for wrapped grid.
+    pub fn grid(&self) -> &G { &self.grid }
+
+    /// Returns recorded row of given generation.
+    pub fn row(&self, generation: u32) -> &[G::Cell] {
+        let start = generation as usize * self.cols as usize;
+        let end = start + self.cols as usize;
+
+        &self.history[start..end]
+    }
+
+    fn record(&mut self) {
+        let generation = self.generations;
+
+        self.history.extend(self.grid.cells().iter().map(|cell| {
+            let mut cell = cell.clone();
+            let coord = GridCoord::from_2d(cell.coord().x(), generation);
+            cell.set_coord(&coord);
+
+            cell
+        }));
+
+        self.generations += 1;
+    }
+}
+
+
+impl<G> Grid for SpaceTime<G>
+    where G: Grid,
+          G::Cell: Clone,
+{
+    type Cell = G::Cell;
+    type Coord = GridCoord;
+
+    fn update(&mut self) {
+        self.grid.update();
+        self.record();
+    }
+
+    /// Changes wrapped grid and rewrites the last row
+    /// of diagram, so it is consistent with grid.
+    fn set_cells(&mut self, new_cells: Vec<Self::Cell>) {
+        self.grid.set_cells(new_cells);
+
+        let start = self.history.len() - self.cols as usize;
+        self.history.truncate(start);
+        self.generations -= 1;
+        self.record();
+    }
+
+    /// Takes whole diagram. Wrapped grid gets its last row.
+    fn replace_cells(&mut self, cells: Vec<Self::Cell>) {
+        let cols = self.cols as usize;
+        assert!(cols > 0 && cells.len() % cols == 0 && !cells.is_empty(),
+                "Diagram should consist of whole rows.");
+
+        let row = cells[cells.len() - cols..]
+            .iter()
+            .map(|cell| {
+                let mut cell = cell.clone();
+                let coord = GridCoord::from_2d(cell.coord().x(), 0);
+                cell.set_coord(&coord);
+
+                cell
+            })
+            .collect();
+        self.grid.replace_cells(row);
+
+        self.generations = (cells.len() / cols) as i32;
+        self.history = cells;
+    }
+
+    fn cells(&self) -> &[Self::Cell] { &self.history }
+
+    fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { self.grid.state() }
+
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) {
+        self.grid.set_state(state);
+    }
+
+    fn size(&self) -> Self::Coord { GridCoord::from_2d(self.cols, self.generations) }
+
+    fn changed(&self) -> Option<usize> { self.grid.changed() }
+}
@@ -0,0 +1,92 @@
+#![cfg(test)]
+use traits::Cell;
+use traits::Neighbors;
+use traits::Coord;
+use traits::Grid;
+use grid::nhood::LineNhood;
+use grid::onedim::OnedimGrid;
+use grid::onedim::SpaceTime;
+use grid::Boundary;
+use grid::EmptyState;
+
+/// Elementary automaton with rule 90:
+/// new state is XOR of left and right neighbors.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Rule90 {
+    coord: (i32, i32),
+    alive: bool,
+}
+
+impl Cell for Rule90 {
+    type Coord = (i32, i32);
+    type State = EmptyState;
+
+    fn update<'a, I>(&'a mut self, _: &'a Self, neighbors: I, _: &Self::State)
+        where I: Neighbors<'a, Self>,
+    {
+        self.alive = neighbors.fold(false, |acc, n| acc ^ n.map_or(false, |n| n.alive));
+    }
+
+    fn with_coord<C: Coord>(coord: C) -> Self {
+        Rule90 {
+            coord: (coord.x(), coord.y()),
+            alive: false,
+        }
+    }
+
+    fn coord(&self) -> &Self::Coord { &self.coord }
+
+    fn set_coord<C: Coord>(&mut self, coord: &C) { self.coord = (coord.x(), coord.y()); }
+}
+
+
+fn to_string(cells: &[Rule90]) -> String {
+    cells.iter().map(|c| if c.alive { '#' } else { '.' }).collect()
+}
+
+
+#[test]
+fn test_rule_90() {
+    let nhood = LineNhood::new(1);
+    let mut grid: OnedimGrid<Rule90, _, _> =
+        OnedimGrid::new(7, nhood, Boundary::Open, EmptyState, 2);
+
+    grid.set_cells(vec![Rule90 {
+                            coord: (3, 0),
+                            alive: true,
+                        }]);
+
+    let mut space_time = SpaceTime::new(grid);
+    for _ in 0..3 {
+        space_time.update();
+    }
+
+    assert_eq!(space_time.size().x(), 7);
+    assert_eq!(space_time.size().y(), 4);
+    assert_eq!(space_time.cells().len(), 28);
+
+    assert_eq!(to_string(space_time.row(0)), "...#...");
+    assert_eq!(to_string(space_time.row(1)), "..#.#..");
+    assert_eq!(to_string(space_time.row(2)), ".#...#.");
+    assert_eq!(to_string(space_time.row(3)), "#.#.#.#");
+
+    // Cells of diagram know their generation.
+    assert!(space_time.row(2).iter().all(|c| c.coord().y() == 2));
+    assert_eq!(space_time.row(3)[6].coord().x(), 6);
+}
+
+
+#[test]
+fn test_torus() {
+    let nhood = LineNhood::new(1);
+    let mut grid: OnedimGrid<Rule90, _, _> =
+        OnedimGrid::new(5, nhood, Boundary::Torus, EmptyState, 1);
+
+    grid.set_cells(vec![Rule90 {
+                            coord: (0, 0),
+                            alive: true,
+                        }]);
+    grid.update();
+
+    assert_eq!(to_string(grid.cells()), ".#..#");
+}
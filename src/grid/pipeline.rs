@@ -0,0 +1,53 @@
+//! Generations made of several phases.
+
+use traits::Cell;
+
+use super::iter::Iter;
+
+
+/// One phase of generation. Updates cell from its version
+/// left by previous phase and neighbors as they were
+/// after previous phase.
+pub type Phase<C> = for<'a> fn(&'a mut C, &'a C, Iter<'a, C>, &<C as Cell>::State);
+
+
+/// `Cell::update` as phase.
+pub fn update<'a, C: Cell>(cell: &'a mut C, old: &'a C, neighbors: Iter<'a, C>, state: &C::State) {
+    cell.update(old, neighbors, state);
+}
+
+
+/// Ordered phases of one generation. Every phase is run
+/// over all cells as its own synchronous step, so cells
+/// need not branch on current phase. Evolution state is
+/// updated once per generation, after the last phase.
+pub struct Pipeline<C: Cell> {
+    phases: Vec<Phase<C>>,
+}
+
+
+impl<C: Cell> Pipeline<C> {
+    /// Constructs empty pipeline. Grid with empty pipeline
+    /// uses `Cell::update` as the only phase.
+    pub fn new() -> Self { Pipeline { phases: Vec::new() } }
+
+    /// Appends phase.
+    pub fn then(mut self, phase: Phase<C>) -> Self {
+        self.phases.push(phase);
+        self
+    }
+
+    /// Returns phases in order they are run.
+    pub fn phases(&self) -> &[Phase<C>] { &self.phases }
+
+    /// Returns count of phases.
+    pub fn len(&self) -> usize { self.phases.len() }
+
+    /// Returns `true` if there are no phases.
+    pub fn is_empty(&self) -> bool { self.phases.is_empty() }
+}
+
+
+impl<C: Cell> Default for Pipeline<C> {
+    fn default() -> Self { Pipeline::new() }
+}
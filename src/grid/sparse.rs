@@ -0,0 +1,315 @@
+//! Unbounded grid which stores only active part of plane.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use traits::Grid;
+use traits::Cell;
+use traits::EvolutionState;
+use traits::Nhood;
+use traits::Coord;
+use grid::twodim::GridCoord;
+
+use super::buffer::DoubleBuffer;
+
+
+/// Side of square tile.
+const TILE: i32 = 16;
+/// Count of cells in one tile.
+const TILE_LEN: usize = (TILE * TILE) as usize;
+
+
+/// Unbounded 2D grid. Plane is split into square tiles
+/// and only tiles around non-quiescent cells are stored.
+/// Cell is quiescent if it equals to cell constructed with
+/// `Cell::with_coord` (the one grid fills new tiles with).
+///
+/// Before every update grid allocates tiles which can be
+/// reached by neighborhood of any non-quiescent cell and frees
+/// tiles which cannot. Neighbors in tiles which are not
+/// allocated are `None`. Allocated tiles are stored one by one
+/// in one-dimensional `Vec` and updated as in `TwodimGrid`.
+pub struct SparseGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone + PartialEq,
+          N: Nhood<Coord = GridCoord>,
+          Es: EvolutionState,
+{
+    buffer: DoubleBuffer<C>,
+    evolution_state: Es,
+    nhood: N,
+    reach: i32,
+    tiles: HashMap<(i32, i32), usize>,
+    keys: Vec<(i32, i32)>,
+}
+
+
+impl<C, N, Es> SparseGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone + PartialEq,
+          N: Nhood<Coord = GridCoord>,
+          Es: EvolutionState,
+{
+    /// Constructs empty SparseGrid with given neighborhood
+    /// strategy, initial evolution state, threads count.
+    pub fn new(nhood: N, state: C::State, threads: u32) -> Self {
+
+        let reach = nhood.neighbors(&GridCoord::from_2d(0, 0))
+            .iter()
+            .map(|c| cmp::max(c.x().abs(), c.y().abs()))
+            .max()
+            .unwrap_or(0);
+
+        let mut buffer = DoubleBuffer::new(0, threads);
+        buffer.split();
+
+        SparseGrid {
+            buffer: buffer,
+            evolution_state: state,
+            nhood: nhood,
+            reach: reach,
+            tiles: HashMap::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    /// Enables counting of changed cells (see `Grid::changed`).
+    pub fn count_changes(&mut self) { self.buffer.count_changes(); }
+
+    /// Returns count of allocated tiles.
+    pub fn tiles_count(&self) -> usize { self.keys.len() }
+
+    /// Returns cell with given coord if it is allocated.
+    pub fn get(&self, x: i32, y: i32) -> Option<&C> {
+        self.index(x, y).map(|index| &self.buffer.cells[index])
+    }
+
+    #[inline]
+    fn is_quiescent(cell: &C) -> bool { *cell == C::with_coord(cell.coord().clone()) }
+
+    /// Allocates tiles which are reachable from non-quiescent
+    /// cells or listed in `extra` and frees other ones.
+    fn reshape(&mut self, extra: &[(i32, i32)]) {
+
+        let mut required = HashSet::new();
+
+        for (index, cell) in self.buffer.cells.iter().enumerate() {
+
+            if Self::is_quiescent(cell) {
+                continue;
+            }
+
+            let coord = self.coord(index);
+            let (left, _) = split(coord.x() - self.reach);
+            let (right, _) = split(coord.x() + self.reach);
+            let (top, _) = split(coord.y() - self.reach);
+            let (bottom, _) = split(coord.y() + self.reach);
+
+            for tile_y in top..bottom + 1 {
+                for tile_x in left..right + 1 {
+                    required.insert((tile_x, tile_y));
+                }
+            }
+        }
+
+        required.extend(extra.iter().cloned());
+
+        let mut keys: Vec<_> = self.keys
+            .iter()
+            .filter(|key| required.contains(key))
+            .cloned()
+            .collect();
+
+        let mut new_keys: Vec<_> = required.into_iter()
+            .filter(|key| !self.tiles.contains_key(key))
+            .collect();
+        new_keys.sort();
+
+        keys.extend(new_keys);
+
+        if keys != self.keys {
+            self.rebuild(keys);
+        }
+    }
+
+    /// Lays out cells of given tiles and inits
+    /// neighbors of every cell.
+    fn rebuild(&mut self, keys: Vec<(i32, i32)>) {
+
+        let mut cells = Vec::with_capacity(keys.len() * TILE_LEN);
+
+        for key in &keys {
+            match self.tiles.get(key) {
+                Some(&block) => {
+                    let start = block * TILE_LEN;
+                    cells.extend_from_slice(&self.buffer.cells[start..start + TILE_LEN]);
+                },
+                None => {
+                    for local in 0..TILE_LEN {
+                        cells.push(C::with_coord(tile_coord(key, local)));
+                    }
+                },
+            }
+        }
+
+        self.tiles = keys.iter().enumerate().map(|(block, key)| (*key, block)).collect();
+        self.keys = keys;
+
+        let mut neighbors = Vec::with_capacity(cells.len());
+        let mut patterns = Vec::with_capacity(cells.len());
+
+        for index in 0..cells.len() {
+            let coord = self.coord(index);
+            neighbors.push(self.get_neighbors(&coord));
+            let offsets = self.nhood.offsets(&coord);
+            let weights = self.nhood.weights(&coord);
+            patterns.push(self.buffer.pattern(offsets, weights));
+        }
+
+        self.buffer.neighbors = neighbors;
+        self.buffer.patterns = patterns;
+        self.buffer.old_cells = cells.clone();
+        self.buffer.cells = cells;
+        self.buffer.split();
+    }
+
+    fn get_neighbors(&self, coord: &GridCoord) -> Vec<Option<usize>> {
+        self.nhood
+            .neighbors(coord)
+            .iter()
+            .map(|coord| self.index(coord.x(), coord.y()))
+            .collect()
+    }
+
+    #[inline]
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let (tile_x, local_x) = split(x);
+        let (tile_y, local_y) = split(y);
+
+        self.tiles
+            .get(&(tile_x, tile_y))
+            .map(|block| block * TILE_LEN + (local_y * TILE + local_x) as usize)
+    }
+
+    #[inline]
+    fn coord(&self, index: usize) -> GridCoord {
+        let key = &self.keys[index / TILE_LEN];
+        tile_coord(key, index % TILE_LEN)
+    }
+}
+
+
+/// Splits coordinate component into tile and
+/// position inside of tile.
+#[inline]
+fn split(value: i32) -> (i32, i32) {
+    let tile = if value >= 0 {
+        value / TILE
+    } else {
+        (value + 1) / TILE - 1
+    };
+
+    (tile, value - tile * TILE)
+}
+
+/// Returns coord of cell with given offset inside of tile.
+#[inline]
+fn tile_coord(key: &(i32, i32), local: usize) -> GridCoord {
+    let local = local as i32;
+    GridCoord::from_2d(key.0 * TILE + local % TILE, key.1 * TILE + local / TILE)
+}
+
+
+impl<C, N, Es> Grid for SparseGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone + PartialEq,
+          N: Nhood<Coord = GridCoord>,
+          Es: EvolutionState + Send,
+{
+    type Cell = C;
+    type Coord = GridCoord;
+
+    fn update(&mut self) {
+        self.reshape(&[]);
+        self.buffer.update(None, &self.evolution_state);
+        self.evolution_state.update();
+    }
+
+    /// Cells can have any coords. Tiles for them
+    /// are allocated if needed.
+    fn set_cells(&mut self, new_cells: Vec<Self::Cell>) {
+
+        let missing: Vec<_> = new_cells.iter()
+            .map(|cell| (split(cell.coord().x()).0, split(cell.coord().y()).0))
+            .filter(|key| !self.tiles.contains_key(key))
+            .collect();
+
+        if !missing.is_empty() {
+            self.reshape(&missing);
+        }
+
+        for cell in new_cells.into_iter() {
+            let index = self.index(cell.coord().x(), cell.coord().y())
+                .expect("tile should be allocated");
+
+            self.buffer.cells[index] = cell;
+        }
+    }
+
+    /// Frees all tiles before cells are set.
+    fn replace_cells(&mut self, cells: Vec<Self::Cell>) {
+        self.rebuild(Vec::new());
+        self.set_cells(cells);
+    }
+
+    /// Returns cells of all allocated tiles.
+    fn cells(&self) -> &[Self::Cell] { &self.buffer.cells }
+
+    fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { &self.evolution_state }
+
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) {
+        self.evolution_state = state;
+    }
+
+    /// Returns width and height of area covered with allocated tiles.
+    fn size(&self) -> Self::Coord {
+        if self.keys.is_empty() {
+            return GridCoord::from_2d(0, 0);
+        }
+
+        let min_x = self.keys.iter().map(|key| key.0).min().unwrap();
+        let max_x = self.keys.iter().map(|key| key.0).max().unwrap();
+        let min_y = self.keys.iter().map(|key| key.1).min().unwrap();
+        let max_y = self.keys.iter().map(|key| key.1).max().unwrap();
+
+        GridCoord::from_2d((max_x - min_x + 1) * TILE, (max_y - min_y + 1) * TILE)
+    }
+
+    fn changed(&self) -> Option<usize> { self.buffer.changed() }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::split;
+    use super::tile_coord;
+    use super::TILE;
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split(0), (0, 0));
+        assert_eq!(split(TILE - 1), (0, TILE - 1));
+        assert_eq!(split(TILE), (1, 0));
+        assert_eq!(split(-1), (-1, TILE - 1));
+        assert_eq!(split(-TILE), (-1, 0));
+        assert_eq!(split(-TILE - 1), (-2, TILE - 1));
+    }
+
+    #[test]
+    fn test_tile_coord() {
+        use traits::Coord;
+
+        let coord = tile_coord(&(-1, 2), (TILE + 3) as usize);
+        assert_eq!(coord.x(), -TILE + 3);
+        assert_eq!(coord.y(), 2 * TILE + 1);
+    }
+}
@@ -0,0 +1,72 @@
+use traits::Coord;
+
+
+/// Coordinate for `ThreedimGrid`. Like `GridCoord`
+/// it can be constructed from offset in one-dimensional
+/// `Vec` and grid size.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct GridCoord3 {
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+
+impl GridCoord3 {
+    /// Constructs GridCoord3 from given offset (in one-dimensional
+    /// array or `Vec`) and grid size.
+    #[inline]
+    pub fn from_offset(offset: u32, layers: u32, rows: u32, cols: u32) -> GridCoord3 {
+        let col = offset % cols;
+        let row = (offset / cols) % rows;
+        let layer = offset / (cols * rows);
+
+        debug_assert!(layer < layers);
+
+        GridCoord3 {
+            x: col as i32,
+            y: row as i32,
+            z: layer as i32,
+        }
+    }
+}
+
+
+impl Coord for GridCoord3 {
+    fn from_2d(x: i32, y: i32) -> Self { GridCoord3 { x: x, y: y, z: 0 } }
+
+    fn from_3d(x: i32, y: i32, z: i32) -> Self { GridCoord3 { x: x, y: y, z: z } }
+
+    fn x(&self) -> i32 { self.x }
+
+    fn y(&self) -> i32 { self.y }
+
+    fn z(&self) -> i32 { self.z }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use traits::Coord;
+    use super::GridCoord3;
+
+    #[test]
+    fn test_from_offset() {
+        let c = GridCoord3::from_offset(0, 2, 3, 4);
+        assert_eq!((c.x(), c.y(), c.z()), (0, 0, 0));
+
+        let c = GridCoord3::from_offset(5, 2, 3, 4);
+        assert_eq!((c.x(), c.y(), c.z()), (1, 1, 0));
+
+        let c = GridCoord3::from_offset(12, 2, 3, 4);
+        assert_eq!((c.x(), c.y(), c.z()), (0, 0, 1));
+
+        let c = GridCoord3::from_offset(23, 2, 3, 4);
+        assert_eq!((c.x(), c.y(), c.z()), (3, 2, 1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_wrong_offset() { GridCoord3::from_offset(24, 2, 3, 4); }
+}
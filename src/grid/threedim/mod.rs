@@ -0,0 +1,158 @@
+//! 3D grid with neighbors iter and custom internal coordinate.
+
+mod coord;
+mod test;
+
+use traits::Grid;
+use traits::Cell;
+use traits::EvolutionState;
+use traits::Nhood;
+use traits::Coord;
+use grid::Boundary;
+
+use super::buffer::DoubleBuffer;
+pub use self::coord::GridCoord3;
+
+/// 3D grid. Cells are stored layer by layer in one-dimensional
+/// `Vec`, every layer is stored as in `TwodimGrid`.
+/// Update process is the same as in `TwodimGrid`.
+pub struct ThreedimGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = GridCoord3>,
+          Es: EvolutionState,
+{
+    buffer: DoubleBuffer<C>,
+    evolution_state: Es,
+    nhood: N,
+    boundary: Boundary<C>,
+    dimensions: GridCoord3,
+    layers: u32,
+    rows: u32,
+    cols: u32,
+}
+
+
+impl<C, N, Es> ThreedimGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = GridCoord3>,
+          Es: EvolutionState,
+{
+    /// Constructs ThreedimGrid with given LAYERSxROWSxCOLS, neighborhood
+    /// strategy, boundary policy, initial evolution state, threads count.
+    pub fn new(layers: u32,
+               rows: u32,
+               cols: u32,
+               nhood: N,
+               boundary: Boundary<C>,
+               state: C::State,
+               threads: u32)
+               -> Self {
+
+        let len = (layers * rows * cols) as usize;
+
+        let mut grid = ThreedimGrid {
+            buffer: DoubleBuffer::new(len, threads),
+            evolution_state: state,
+            nhood: nhood,
+            boundary: boundary,
+            layers: layers,
+            rows: rows,
+            cols: cols,
+            dimensions: GridCoord3::from_3d(cols as i32, rows as i32, layers as i32),
+        };
+
+        grid.init();
+
+        grid
+    }
+
+    /// Enables counting of changed cells (see `Grid::changed`).
+    pub fn count_changes(&mut self)
+        where C: PartialEq,
+    {
+        self.buffer.count_changes();
+    }
+
+    fn init(&mut self) {
+
+        let cells_count = self.layers * self.rows * self.cols;
+
+        for offset in 0..cells_count {
+
+            let coord = GridCoord3::from_offset(offset, self.layers, self.rows, self.cols);
+
+            let neighbors = self.get_neighbors(&coord);
+            let offsets = self.nhood.offsets(&coord);
+            let weights = self.nhood.weights(&coord);
+            let cell = C::with_coord(coord);
+
+            self.buffer.push(cell, neighbors, offsets, weights);
+        }
+
+        self.buffer.split();
+    }
+
+    fn get_neighbors(&self, coord: &GridCoord3) -> Vec<Option<usize>> {
+
+        let neighbors_count = self.nhood.neighbors_count();
+        let mut neighbors = Vec::with_capacity(neighbors_count);
+
+        let cols = self.cols as i32;
+        let rows = self.rows as i32;
+        let layers = self.layers as i32;
+
+        for coord in &self.nhood.neighbors(coord) {
+
+            let resolved =
+                self.boundary.resolve_3d(coord.x(), coord.y(), coord.z(), cols, rows, layers);
+            let neighbor = resolved.map(|(x, y, z)| self.offset(&GridCoord3::from_3d(x, y, z)));
+
+            neighbors.push(neighbor);
+        }
+
+        neighbors
+    }
+
+    #[inline]
+    fn offset<Crd: Coord>(&self, coord: &Crd) -> usize {
+        let cols = self.cols as i32;
+        let rows = self.rows as i32;
+
+        ((coord.z() * rows + coord.y()) * cols + coord.x()) as usize
+    }
+}
+
+
+impl<C, N, Es> Grid for ThreedimGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = GridCoord3>,
+          Es: EvolutionState + Send,
+{
+    type Cell = C;
+    type Coord = GridCoord3;
+
+    fn update(&mut self) {
+        self.buffer.update(self.boundary.ghost(), &self.evolution_state);
+        self.evolution_state.update();
+    }
+
+    fn set_cells(&mut self, new_cells: Vec<Self::Cell>) {
+
+        for cell in new_cells.into_iter() {
+            let index = self.offset(cell.coord());
+            self.buffer.cells[index] = cell;
+        }
+    }
+
+    fn cells(&self) -> &[Self::Cell] { &self.buffer.cells }
+
+    fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { &self.evolution_state }
+
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) {
+        self.evolution_state = state;
+    }
+
+    fn size(&self) -> Self::Coord { self.dimensions.clone() }
+
+    fn changed(&self) -> Option<usize> { self.buffer.changed() }
+}
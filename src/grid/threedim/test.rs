@@ -0,0 +1,81 @@
+#![cfg(test)]
+use traits::Cell;
+use traits::Neighbors;
+use traits::Coord;
+use traits::Grid;
+use grid::nhood::MooreNhood3;
+use grid::nhood::VonNeumannNhood3;
+use grid::threedim::ThreedimGrid;
+use grid::Boundary;
+use grid::EmptyState;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct CountTestCell {
+    coord: (i32, i32, i32),
+    alive: bool,
+    neighbors: usize,
+}
+
+impl Cell for CountTestCell {
+    type Coord = (i32, i32, i32);
+    type State = EmptyState;
+
+    fn update<'a, I>(&'a mut self, _: &'a Self, neighbors: I, _: &Self::State)
+        where I: Neighbors<'a, Self>,
+    {
+        self.neighbors = neighbors.filter(|n| n.map_or(false, |n| n.alive)).count();
+    }
+
+    fn with_coord<C: Coord>(coord: C) -> Self {
+        CountTestCell {
+            coord: (coord.x(), coord.y(), coord.z()),
+            alive: true,
+            neighbors: 0,
+        }
+    }
+
+    fn coord(&self) -> &Self::Coord { &self.coord }
+
+    fn set_coord<C: Coord>(&mut self, coord: &C) {
+        self.coord = (coord.x(), coord.y(), coord.z());
+    }
+}
+
+
+#[test]
+fn test_moore_neighbors() {
+    let nhood = MooreNhood3::new();
+    let mut grid: ThreedimGrid<CountTestCell, _, _> =
+        ThreedimGrid::new(3, 3, 3, nhood, Boundary::Open, EmptyState, 2);
+    grid.update();
+
+    let find = |x, y, z| grid.cells().iter().find(|c| c.coord == (x, y, z)).unwrap().neighbors;
+
+    assert_eq!(find(1, 1, 1), 26);
+    assert_eq!(find(0, 0, 0), 7);
+    assert_eq!(find(1, 0, 0), 11);
+    assert_eq!(find(1, 1, 0), 17);
+}
+
+
+#[test]
+fn test_von_neumann_neighbors() {
+    let nhood = VonNeumannNhood3::new();
+    let mut grid: ThreedimGrid<CountTestCell, _, _> =
+        ThreedimGrid::new(2, 3, 4, nhood, Boundary::Torus, EmptyState, 1);
+
+    // Cell at the last layer should see the first one through boundary.
+    let dead = CountTestCell {
+        coord: (2, 1, 0),
+        alive: false,
+        neighbors: 0,
+    };
+    grid.set_cells(vec![dead]);
+    grid.update();
+
+    let find = |x, y, z| grid.cells().iter().find(|c| c.coord == (x, y, z)).unwrap().neighbors;
+
+    assert_eq!(find(0, 0, 0), 6);
+    assert_eq!(find(2, 1, 1), 4);
+    assert_eq!(find(3, 1, 0), 5);
+}
@@ -0,0 +1,93 @@
+use traits::Coord;
+
+
+/// Orientation of triangle in triangular tiling.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Orientation {
+    /// Triangle with apex at the top and edge at the bottom.
+    Up,
+    /// Triangle with edge at the top and apex at the bottom.
+    Down,
+}
+
+
+impl Orientation {
+    /// Returns orientation of triangle with given coord.
+    /// Triangles alternate in every row and in every
+    /// column, triangle at `(0, 0)` points up.
+    #[inline]
+    pub fn of<C: Coord>(coord: &C) -> Orientation {
+        if (coord.x() + coord.y()) % 2 == 0 {
+            Orientation::Up
+        } else {
+            Orientation::Down
+        }
+    }
+}
+
+
+/// Coordinate for `TriGrid`: column and row of triangle.
+/// Orientation of triangle is encoded in parity of
+/// coordinate (see `Orientation::of`).
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TriCoord {
+    x: i32,
+    y: i32,
+}
+
+
+impl TriCoord {
+    /// Constructs TriCoord from given offset (in one-dimensional
+    /// array or `Vec`) and grid size.
+    #[inline]
+    pub fn from_offset(offset: u32, rows: u32, cols: u32) -> TriCoord {
+        let col = offset % cols;
+        let row = offset / cols;
+
+        debug_assert!(row < rows);
+
+        TriCoord {
+            x: col as i32,
+            y: row as i32,
+        }
+    }
+
+    /// Returns orientation of triangle.
+    #[inline]
+    pub fn orientation(&self) -> Orientation { Orientation::of(self) }
+}
+
+
+impl Coord for TriCoord {
+    fn from_2d(x: i32, y: i32) -> Self { TriCoord { x: x, y: y } }
+
+    fn x(&self) -> i32 { self.x }
+
+    fn y(&self) -> i32 { self.y }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use traits::Coord;
+    use super::Orientation;
+    use super::TriCoord;
+
+    #[test]
+    fn test_orientation() {
+        assert_eq!(TriCoord::from_2d(0, 0).orientation(), Orientation::Up);
+        assert_eq!(TriCoord::from_2d(1, 0).orientation(), Orientation::Down);
+        assert_eq!(TriCoord::from_2d(0, 1).orientation(), Orientation::Down);
+        assert_eq!(TriCoord::from_2d(3, 5).orientation(), Orientation::Up);
+        assert_eq!(TriCoord::from_2d(-1, 0).orientation(), Orientation::Down);
+        assert_eq!(Orientation::of(&(-2, 1)), Orientation::Down);
+    }
+
+    #[test]
+    fn test_from_offset() {
+        let c = TriCoord::from_offset(7, 3, 4);
+        assert_eq!((c.x(), c.y()), (3, 1));
+        assert_eq!(c.orientation(), Orientation::Up);
+    }
+}
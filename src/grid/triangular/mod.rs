@@ -0,0 +1,153 @@
+//! Grid of triangular tiling.
+
+mod coord;
+mod test;
+
+use traits::Grid;
+use traits::Cell;
+use traits::EvolutionState;
+use traits::Nhood;
+use traits::Coord;
+use grid::Boundary;
+
+use super::buffer::DoubleBuffer;
+pub use self::coord::Orientation;
+pub use self::coord::TriCoord;
+
+/// Triangular grid. Every row consists of triangles pointing
+/// up and down in turn (see `TriCoord`). Cells are stored row
+/// by row in one-dimensional `Vec`. Update process is the same
+/// as in `TwodimGrid`. Wrapping boundary policies keep orientation
+/// of neighbors only if counts of rows and cols are even.
+pub struct TriGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = TriCoord>,
+          Es: EvolutionState,
+{
+    buffer: DoubleBuffer<C>,
+    evolution_state: Es,
+    nhood: N,
+    boundary: Boundary<C>,
+    dimensions: TriCoord,
+    rows: u32,
+    cols: u32,
+}
+
+
+impl<C, N, Es> TriGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = TriCoord>,
+          Es: EvolutionState,
+{
+    /// Constructs TriGrid with given ROWSxCOLS, neighborhood
+    /// strategy, boundary policy, initial evolution state, threads count.
+    pub fn new(rows: u32,
+               cols: u32,
+               nhood: N,
+               boundary: Boundary<C>,
+               state: C::State,
+               threads: u32)
+               -> Self {
+
+        let len = (rows * cols) as usize;
+
+        let mut grid = TriGrid {
+            buffer: DoubleBuffer::new(len, threads),
+            evolution_state: state,
+            nhood: nhood,
+            boundary: boundary,
+            rows: rows,
+            cols: cols,
+            dimensions: TriCoord::from_2d(cols as i32, rows as i32),
+        };
+
+        grid.init();
+
+        grid
+    }
+
+    /// Enables counting of changed cells (see `Grid::changed`).
+    pub fn count_changes(&mut self)
+        where C: PartialEq,
+    {
+        self.buffer.count_changes();
+    }
+
+    fn init(&mut self) {
+
+        let cells_count = self.rows * self.cols;
+
+        for offset in 0..cells_count {
+
+            let coord = TriCoord::from_offset(offset, self.rows, self.cols);
+
+            let neighbors = self.get_neighbors(&coord);
+            let offsets = self.nhood.offsets(&coord);
+            let weights = self.nhood.weights(&coord);
+            let cell = C::with_coord(coord);
+
+            self.buffer.push(cell, neighbors, offsets, weights);
+        }
+
+        self.buffer.split();
+    }
+
+    fn get_neighbors(&self, coord: &TriCoord) -> Vec<Option<usize>> {
+
+        let neighbors_count = self.nhood.neighbors_count();
+        let mut neighbors = Vec::with_capacity(neighbors_count);
+
+        let cols = self.cols as i32;
+        let rows = self.rows as i32;
+
+        for coord in &self.nhood.neighbors(coord) {
+
+            let resolved = self.boundary.resolve(coord.x(), coord.y(), cols, rows);
+            let neighbor = resolved.map(|(x, y)| self.offset(&TriCoord::from_2d(x, y)));
+
+            neighbors.push(neighbor);
+        }
+
+        neighbors
+    }
+
+    #[inline]
+    fn offset<Crd: Coord>(&self, coord: &Crd) -> usize {
+        (coord.y() * self.cols as i32 + coord.x()) as usize
+    }
+}
+
+
+impl<C, N, Es> Grid for TriGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = TriCoord>,
+          Es: EvolutionState + Send,
+{
+    type Cell = C;
+    type Coord = TriCoord;
+
+    fn update(&mut self) {
+        self.buffer.update(self.boundary.ghost(), &self.evolution_state);
+        self.evolution_state.update();
+    }
+
+    fn set_cells(&mut self, new_cells: Vec<Self::Cell>) {
+
+        for cell in new_cells.into_iter() {
+            let index = self.offset(cell.coord());
+            self.buffer.cells[index] = cell;
+        }
+    }
+
+    fn cells(&self) -> &[Self::Cell] { &self.buffer.cells }
+
+    fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { &self.evolution_state }
+
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) {
+        self.evolution_state = state;
+    }
+
+    fn size(&self) -> Self::Coord { self.dimensions.clone() }
+
+    fn changed(&self) -> Option<usize> { self.buffer.changed() }
+}
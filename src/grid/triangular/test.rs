@@ -0,0 +1,78 @@
+#![cfg(test)]
+use traits::Cell;
+use traits::Neighbors;
+use traits::Coord;
+use traits::Grid;
+use grid::nhood::TriEdgeNhood;
+use grid::nhood::TriVertexNhood;
+use grid::triangular::TriGrid;
+use grid::Boundary;
+use grid::EmptyState;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct CountTestCell {
+    coord: (i32, i32),
+    neighbors: usize,
+}
+
+impl Cell for CountTestCell {
+    type Coord = (i32, i32);
+    type State = EmptyState;
+
+    fn update<'a, I>(&'a mut self, _: &'a Self, neighbors: I, _: &Self::State)
+        where I: Neighbors<'a, Self>,
+    {
+        self.neighbors = neighbors.filter(|n| n.is_some()).count();
+    }
+
+    fn with_coord<C: Coord>(coord: C) -> Self {
+        CountTestCell {
+            coord: (coord.x(), coord.y()),
+            neighbors: 0,
+        }
+    }
+
+    fn coord(&self) -> &Self::Coord { &self.coord }
+
+    fn set_coord<C: Coord>(&mut self, coord: &C) { self.coord = (coord.x(), coord.y()); }
+}
+
+
+fn counts<G: Grid<Cell = CountTestCell>>(grid: &G) -> Vec<usize> {
+    grid.cells().iter().map(|c| c.neighbors).collect()
+}
+
+
+#[test]
+fn test_edge_neighbors() {
+    // /\ \/ /\ \/
+    // \/ /\ \/ /\
+    let nhood = TriEdgeNhood::new();
+    let mut grid: TriGrid<CountTestCell, _, _> =
+        TriGrid::new(2, 4, nhood, Boundary::Open, EmptyState, 1);
+    grid.update();
+
+    assert_eq!(counts(&grid), vec![2, 2, 3, 1, 2, 2, 3, 1]);
+}
+
+
+#[test]
+fn test_vertex_neighbors() {
+    let nhood = TriVertexNhood::new();
+    let mut grid: TriGrid<CountTestCell, _, _> =
+        TriGrid::new(4, 6, nhood, Boundary::Torus, EmptyState, 2);
+    grid.update();
+
+    assert!(counts(&grid).iter().all(|&count| count == 12));
+
+    let mut grid: TriGrid<CountTestCell, _, _> =
+        TriGrid::new(4, 6, TriVertexNhood::new(), Boundary::Open, EmptyState, 2);
+    grid.update();
+
+    // Up triangle in the corner touches only two
+    // triangles in its row and three below.
+    assert_eq!(grid.cells()[0].neighbors, 5);
+    // Inner triangles see all neighbors.
+    assert_eq!(grid.cells()[6 + 2].neighbors, 12);
+    assert_eq!(grid.cells()[6 + 3].neighbors, 12);
+}
@@ -0,0 +1,136 @@
+use std::error::Error;
+use std::fmt;
+
+use traits::Cell;
+use traits::Coord;
+use traits::EvolutionState;
+use traits::Nhood;
+use grid::Boundary;
+use grid::UpdateMode;
+use rng::Rng;
+
+use super::GridCoord;
+use super::TwodimGrid;
+
+
+/// Everything needed to restore `TwodimGrid` and continue
+/// evolution: dimensions, boundary policy, generation,
+/// cells, evolution state, update mode and randomness.
+/// Neighborhood, threads count and pipeline are not
+/// stored, they are given on restore.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Checkpoint<C, Es> {
+    /// Rows count of grid.
+    pub rows: u32,
+    /// Cols count of grid.
+    pub cols: u32,
+    /// Boundary policy of grid.
+    pub boundary: Boundary<C>,
+    /// Generation checkpoint has been taken at.
+    pub generation: u64,
+    /// All cells of grid.
+    pub cells: Vec<C>,
+    /// Evolution state.
+    pub state: Es,
+    /// Update mode of grid.
+    pub mode: UpdateMode,
+    /// Generator of update order in `RandomOrder` mode.
+    pub rng: Rng,
+    /// Seed of random streams of cells.
+    pub seed: u64,
+    /// Updates made by grid. Random streams
+    /// of cells are derived from it.
+    pub steps: u64,
+    /// Probability of cell to be updated.
+    pub probability: f64,
+}
+
+
+/// Errors of restoring `TwodimGrid` from checkpoint.
+#[derive(Debug, PartialEq)]
+pub enum CheckpointError {
+    /// Count of cells does not match dimensions.
+    CellsCount,
+    /// Coord of cell does not match its place in grid.
+    Coord,
+}
+
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.description()) }
+}
+
+
+impl Error for CheckpointError {
+    fn description(&self) -> &str {
+        match *self {
+            CheckpointError::CellsCount => "count of cells does not match dimensions",
+            CheckpointError::Coord => "coord of cell does not match its place",
+        }
+    }
+}
+
+
+impl<C, N, Es> TwodimGrid<C, N, Es>
+    where C: Cell<State = Es> + Clone,
+          N: Nhood<Coord = GridCoord>,
+          Es: EvolutionState + Clone,
+{
+    /// Takes checkpoint of grid. Grid does not count
+    /// generations, so engine should tell it.
+    pub fn checkpoint(&self, generation: u64) -> Checkpoint<C, Es> {
+        Checkpoint {
+            rows: self.rows,
+            cols: self.cols,
+            boundary: self.boundary.clone(),
+            generation: generation,
+            cells: self.buffer.cells.clone(),
+            state: self.evolution_state.clone(),
+            mode: self.mode.clone(),
+            rng: self.rng.clone(),
+            seed: self.buffer.seed,
+            steps: self.buffer.steps,
+            probability: self.buffer.probability,
+        }
+    }
+
+    /// Restores grid from checkpoint with given neighborhood
+    /// strategy and threads count. Checkpoint may come from
+    /// disk or network, so cells are checked to fill grid
+    /// row by row.
+    pub fn from_checkpoint(checkpoint: Checkpoint<C, Es>,
+                           nhood: N,
+                           threads: u32)
+                           -> Result<Self, CheckpointError> {
+
+        let cols = checkpoint.cols as usize;
+        if checkpoint.cells.len() != checkpoint.rows as usize * cols {
+            return Err(CheckpointError::CellsCount);
+        }
+
+        for (index, cell) in checkpoint.cells.iter().enumerate() {
+            let coord = cell.coord();
+            if coord.x() as usize != index % cols || coord.y() as usize != index / cols {
+                return Err(CheckpointError::Coord);
+            }
+        }
+
+        let mut grid = TwodimGrid::new(checkpoint.rows,
+                                       checkpoint.cols,
+                                       nhood,
+                                       checkpoint.boundary,
+                                       checkpoint.state,
+                                       threads);
+
+        for (slot, cell) in grid.buffer.cells.iter_mut().zip(checkpoint.cells) {
+            *slot = cell;
+        }
+        grid.mode = checkpoint.mode;
+        grid.rng = checkpoint.rng;
+        grid.buffer.seed = checkpoint.seed;
+        grid.buffer.steps = checkpoint.steps;
+        grid.buffer.probability = checkpoint.probability;
+
+        Ok(grid)
+    }
+}
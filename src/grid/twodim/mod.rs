@@ -1,44 +1,50 @@
 //! 2D grid with neighbors iter and custom internal coordinate.
 
-mod iter;
 mod coord;
+mod checkpoint;
 mod test;
 
-use std::cmp;
-use std::mem;
-use std::ops::{Deref, DerefMut};
-
-use scoped_threadpool::Pool;
 
 use traits::Grid;
 use traits::Cell;
 use traits::EvolutionState;
 use traits::Nhood;
+use traits::Neighbors;
 use traits::Coord;
-
-use self::iter::Iter;
+use grid::Boundary;
+use grid::UpdateMode;
+use grid::Pipeline;
+use grid::pipeline;
+use grid::Phase;
+use rng::Rng;
+
+use super::buffer::DoubleBuffer;
 pub use self::coord::GridCoord;
+pub use self::checkpoint::Checkpoint;
+pub use self::checkpoint::CheckpointError;
 
 /// 2D grid. Implemented with two buffers.
 /// They are swapped on every evolution step.
 /// Old buffer is used for read-only neighbors data.
 /// New buffer is writable and mutated through update process.
 /// Grid uses one-dimensional `Vec` to store cells.
+/// Cells may be updated asynchronously, see `UpdateMode`.
 pub struct TwodimGrid<C, N, Es>
     where C: Cell<State = Es> + Clone,
           N: Nhood<Coord = GridCoord>,
           Es: EvolutionState,
 {
-    cells: Vec<C>,
-    old_cells: Vec<C>,
+    buffer: DoubleBuffer<C>,
     evolution_state: Es,
     nhood: N,
-    neighbors: Vec<Vec<Option<usize>>>,
+    boundary: Boundary<C>,
+    pipeline: Pipeline<C>,
+    mode: UpdateMode,
+    order: Vec<usize>,
+    rng: Rng,
     dimensions: GridCoord,
     rows: u32,
     cols: u32,
-    pool: Pool,
-    parts: Vec<(usize, usize)>,
 }
 
 
@@ -48,29 +54,29 @@ impl<C, N, Es> TwodimGrid<C, N, Es>
           Es: EvolutionState,
 {
     /// Constructs TwodimGrid with given ROWSxCOLS, neighborhood
-    /// strategy, initial evolution state, threads count.
-    pub fn new(rows: u32, cols: u32, nhood: N, state: C::State, threads: u32) -> Self {
+    /// strategy, boundary policy, initial evolution state, threads count.
+    pub fn new(rows: u32,
+               cols: u32,
+               nhood: N,
+               boundary: Boundary<C>,
+               state: C::State,
+               threads: u32)
+               -> Self {
 
         let len = (rows * cols) as usize;
 
-        let cells = Vec::with_capacity(len);
-        let old_cells = Vec::with_capacity(len);
-        let neighbors = Vec::with_capacity(len);
-
-        let threads = cmp::max(threads, 1);
-        let pool = Pool::new(threads);
-
         let mut grid = TwodimGrid {
-            cells: cells,
-            old_cells: old_cells,
+            buffer: DoubleBuffer::new(len, threads),
             evolution_state: state,
             nhood: nhood,
-            neighbors: neighbors,
+            boundary: boundary,
+            pipeline: Pipeline::new(),
+            mode: UpdateMode::Synchronous,
+            order: (0..len).collect(),
+            rng: Rng::new(0),
             rows: rows,
             cols: cols,
             dimensions: GridCoord::from_2d(cols as i32, rows as i32),
-            pool: pool,
-            parts: Vec::new(),
         };
 
         grid.init();
@@ -78,6 +84,13 @@ impl<C, N, Es> TwodimGrid<C, N, Es>
         grid
     }
 
+    /// Enables counting of changed cells (see `Grid::changed`).
+    pub fn count_changes(&mut self)
+        where C: PartialEq,
+    {
+        self.buffer.count_changes();
+    }
+
     fn init(&mut self) {
 
         let cells_count = self.rows * self.cols;
@@ -86,29 +99,15 @@ impl<C, N, Es> TwodimGrid<C, N, Es>
 
             let coord = GridCoord::from_offset(offset, self.rows, self.cols);
 
-            // init neighbors
             let neighbors = self.get_neighbors(&coord);
-            self.neighbors.push(neighbors);
-
-            // init cells
+            let offsets = self.nhood.offsets(&coord);
+            let weights = self.nhood.weights(&coord);
             let cell = C::with_coord(coord);
-            self.cells.push(cell.clone());
-            self.old_cells.push(cell.clone());
+
+            self.buffer.push(cell, neighbors, offsets, weights);
         }
 
-        // Init split border indices.
-        // Used later in `update` to split `cells` between threads.
-        let cells_on_thread = cells_count / self.pool.thread_count();
-        let mut start;
-        let mut end = 0;
-        // Emulating `do-while` loop.
-        while {
-            start = end;
-            end = cmp::min(start + cells_on_thread, cells_count);
-            self.parts.push((start as usize, end as usize));
-
-            end < cells_count
-        } {}
+        self.buffer.split();
     }
 
     fn get_neighbors(&self, coord: &GridCoord) -> Vec<Option<usize>> {
@@ -121,62 +120,111 @@ impl<C, N, Es> TwodimGrid<C, N, Es>
 
         for coord in &self.nhood.neighbors(coord) {
 
-            if coord.x() >= 0 && coord.x() < cols && coord.y() >= 0 && coord.y() < rows {
-                neighbors.push(Some(self.offset(coord)));
-            } else {
-                neighbors.push(None);
-            }
+            let resolved = self.boundary.resolve(coord.x(), coord.y(), cols, rows);
+            let neighbor = resolved.map(|(x, y)| self.offset(&GridCoord::from_2d(x, y)));
+
+            neighbors.push(neighbor);
         }
 
         neighbors
     }
 
-    #[inline]
-    fn offset<Crd: Coord>(&self, coord: &Crd) -> usize {
-        (coord.y() * self.cols as i32 + coord.x()) as usize
+    /// Sets order in which cells are updated. Grid is
+    /// synchronous by default. Generator of random order is
+    /// reseeded, so evolution can be repeated with the same mode.
+    /// Asynchronous modes update cells in calling thread.
+    pub fn set_update_mode(&mut self, mode: UpdateMode) {
+        self.order = (0..self.buffer.cells.len()).collect();
+        self.rng = match mode {
+            UpdateMode::RandomOrder(seed) => Rng::new(seed),
+            _ => Rng::new(0),
+        };
+        self.mode = mode;
     }
-}
-
-/// Helper struct to enable sendable mutable pointers.
-struct MutPtr<T: ?Sized>(*mut T);
 
-unsafe impl<T: ?Sized> Send for MutPtr<T> {}
-unsafe impl<T: ?Sized> Sync for MutPtr<T> {}
-
-impl<T: ?Sized> Deref for MutPtr<T> {
-    type Target = T;
-    fn deref(&self) -> &T { unsafe { &*self.0 } }
-}
+    /// Returns current update mode.
+    pub fn update_mode(&self) -> &UpdateMode { &self.mode }
+
+    /// Sets phases every generation is made of. Grid
+    /// with empty pipeline (default) uses `Cell::update`.
+    /// `changed` counts cells which differ from cells at
+    /// the start of generation.
+    pub fn set_pipeline(&mut self, pipeline: Pipeline<C>) { self.pipeline = pipeline; }
+
+    /// Returns phases every generation is made of.
+    pub fn pipeline(&self) -> &Pipeline<C> { &self.pipeline }
+
+    /// Sets seed random streams of cells are derived from
+    /// (see `Neighbors::rng`). Seed is 0 by default.
+    pub fn set_seed(&mut self, seed: u64) { self.buffer.seed = seed; }
+
+    /// Sets probability of every cell to be updated on
+    /// generation. Cells which are not updated keep their
+    /// values. Decisions are made with seed of grid, so they
+    /// are reproducible. Probability is 1 by default.
+    pub fn set_update_probability(&mut self, probability: f64) {
+        assert!(probability >= 0.0 && probability <= 1.0,
+                "Probability should be in [0, 1].");
+        self.buffer.probability = probability;
+    }
 
-impl<T: ?Sized> DerefMut for MutPtr<T> {
-    fn deref_mut(&mut self) -> &mut T { unsafe { &mut *self.0 } }
-}
+    /// One step in evolution of rows from `start` to `end`.
+    /// Other rows are left with stale values, so rows the range
+    /// depends on (see `dependencies`) should be set before
+    /// every step. Used to split grid between processes, so
+    /// update should be synchronous and pipeline should not
+    /// have more than one phase.
+    pub fn update_rows(&mut self, start: u32, end: u32) {
+        assert!(self.mode == UpdateMode::Synchronous,
+                "Rows can be updated only synchronously.");
+        assert!(self.pipeline.len() <= 1,
+                "Rows can be updated only in single phase.");
+
+        let start = (start * self.cols) as usize;
+        let end = (end * self.cols) as usize;
+        let phase = self.pipeline.phases().first().cloned().unwrap_or(pipeline::update);
+
+        self.buffer.update_range(self.boundary.ghost(), &self.evolution_state, start, end, phase);
+        self.evolution_state.update();
+    }
 
-#[allow(expl_impl_clone_on_copy)]
-impl<T: ?Sized> Clone for MutPtr<T> {
-    fn clone(&self) -> Self { MutPtr(self.0) }
-}
+    /// Returns rows outside of range from `start` to `end`
+    /// which neighbors of cells in range belong to.
+    pub fn dependencies(&self, start: u32, end: u32) -> Vec<u32> {
+        let mut rows = Vec::new();
 
-impl<T: ?Sized> Copy for MutPtr<T> {}
+        for index in (start * self.cols) as usize..(end * self.cols) as usize {
+            for neighbor in self.buffer.neighbors[index].iter().filter_map(|n| *n) {
+                let row = neighbor as u32 / self.cols;
 
-/// Helper struct to enable sendable const pointers.
-struct ConstPtr<T: ?Sized>(*const T);
+                if (row < start || row >= end) && !rows.contains(&row) {
+                    rows.push(row);
+                }
+            }
+        }
+        rows.sort();
 
-unsafe impl<T: ?Sized> Send for ConstPtr<T> {}
-unsafe impl<T: ?Sized> Sync for ConstPtr<T> {}
+        rows
+    }
 
-impl<T: ?Sized> Deref for ConstPtr<T> {
-    type Target = T;
-    fn deref(&self) -> &T { unsafe { &*self.0 } }
-}
+    /// Computes weighted sum of neighbors of cell with given
+    /// coord (see `Neighbors::weighted_sum`). Every neighbor
+    /// is projected to number first. Neighbors outside of grid
+    /// are taken from boundary.
+    pub fn weighted_sum<Crd, F>(&self, coord: &Crd, projection: F) -> f64
+        where Crd: Coord,
+              F: Fn(&C) -> f64,
+    {
+        let index = self.offset(coord);
+        self.buffer.neighbors_of(index, self.boundary.ghost()).weighted_sum(projection)
+    }
 
-#[allow(expl_impl_clone_on_copy)]
-impl<T: ?Sized> Clone for ConstPtr<T> {
-    fn clone(&self) -> Self { ConstPtr(self.0) }
+    #[inline]
+    fn offset<Crd: Coord>(&self, coord: &Crd) -> usize {
+        (coord.y() * self.cols as i32 + coord.x()) as usize
+    }
 }
 
-impl<T: ?Sized> Copy for ConstPtr<T> {}
-
 
 impl<C, N, Es> Grid for TwodimGrid<C, N, Es>
     where C: Cell<State = Es> + Clone,
@@ -187,33 +235,26 @@ impl<C, N, Es> Grid for TwodimGrid<C, N, Es>
     type Coord = GridCoord;
 
     fn update(&mut self) {
-        mem::swap(&mut self.cells, &mut self.old_cells);
-
-        let mut cells = MutPtr(&mut *self.cells as *mut [C]);
-        let old_cells = ConstPtr(&*self.old_cells as *const [C]);
-        let neighbors = ConstPtr(&*self.neighbors as *const [Vec<Option<usize>>]);
-        let neighbors_count = self.nhood.neighbors_count();
-        let evolution_state = ConstPtr(&self.evolution_state as *const Es);
-
-        let parts = &self.parts;
-
-        self.pool.scoped(|scope| {
-            for &(start, end) in parts {
-                scope.execute(move || {
-                    for i in start..end {
-                        unsafe {
-                            let neighbors = neighbors.get_unchecked(i);
-                            let neighbors_iter = Iter::new(&*old_cells, neighbors, neighbors_count);
-
-                            let old = (*old_cells).get_unchecked(i);
-                            let cell = (*cells).get_unchecked_mut(i);
-                            cell.update(old, neighbors_iter, &*evolution_state);
-                        }
-                    }
-                });
-            }
-        });
+        let ghost = self.boundary.ghost();
+        let default: [Phase<C>; 1] = [pipeline::update];
+        let phases = if self.pipeline.is_empty() { &default[..] } else { self.pipeline.phases() };
+
+        // Permutation is made from scratch, so it depends
+        // only on generator, which checkpoint keeps.
+        if let UpdateMode::RandomOrder(_) = self.mode {
+            self.order = (0..self.buffer.cells.len()).collect();
+            self.rng.shuffle(&mut self.order);
+        }
 
+        match self.mode {
+            UpdateMode::Synchronous => {
+                self.buffer.update_phases(ghost, &self.evolution_state, phases);
+            },
+            UpdateMode::Sweep |
+            UpdateMode::RandomOrder(_) => {
+                self.buffer.update_in_order(ghost, &self.evolution_state, &self.order, phases);
+            },
+        }
         self.evolution_state.update();
     }
 
@@ -227,13 +268,19 @@ impl<C, N, Es> Grid for TwodimGrid<C, N, Es>
                 index = self.offset(cell.coord());
             }
 
-            self.cells[index] = cell;
+            self.buffer.cells[index] = cell;
         }
     }
 
-    fn cells(&self) -> &[Self::Cell] { &self.cells }
+    fn cells(&self) -> &[Self::Cell] { &self.buffer.cells }
 
     fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State { &self.evolution_state }
 
+    fn set_state(&mut self, state: <<Self as Grid>::Cell as Cell>::State) {
+        self.evolution_state = state;
+    }
+
     fn size(&self) -> Self::Coord { self.dimensions.clone() }
+
+    fn changed(&self) -> Option<usize> { self.buffer.changed() }
 }
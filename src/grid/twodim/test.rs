@@ -1,12 +1,17 @@
 #![cfg(test)]
 use traits::Cell;
+use traits::Neighbors;
 use traits::Coord;
 use traits::Grid;
 use grid::nhood::MooreNhood;
+use grid::nhood::KernelNhood;
 use grid::twodim::TwodimGrid;
+use grid::twodim::GridCoord;
 use grid::EmptyState;
+use grid::Boundary;
+use grid::UpdateMode;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 struct MooreTestCell {
     coord: (i32, i32),
 }
@@ -16,7 +21,7 @@ impl Cell for MooreTestCell {
     type State = EmptyState;
 
     fn update<'a, I>(&'a mut self, _: &'a Self, neighbors: I, _: &Self::State)
-        where I: Iterator<Item = Option<&'a Self>>,
+        where I: Neighbors<'a, Self>,
     {
 
         // Initial value should be copied
@@ -53,6 +58,400 @@ impl Cell for MooreTestCell {
 #[test]
 fn test_neighbors() {
     let nhood = MooreNhood::new();
-    let mut grid: TwodimGrid<MooreTestCell, _, _> = TwodimGrid::new(2, 2, nhood, EmptyState, 1);
+    let mut grid: TwodimGrid<MooreTestCell, _, _> =
+        TwodimGrid::new(2, 2, nhood, Boundary::Open, EmptyState, 1);
     grid.update();
 }
+
+
+#[test]
+fn test_torus_neighbors() {
+    let nhood = MooreNhood::new();
+    let grid: TwodimGrid<MooreTestCell, _, _> =
+        TwodimGrid::new(3, 3, nhood, Boundary::Torus, EmptyState, 1);
+
+    // Top-left corner sees cells from opposite edges.
+    let expected = vec![Some(8), Some(6), Some(7), Some(2), Some(1), Some(5), Some(3), Some(4)];
+    assert_eq!(grid.buffer.neighbors[0], expected);
+}
+
+
+#[test]
+fn test_klein_neighbors() {
+    let nhood = MooreNhood::new();
+    let grid: TwodimGrid<MooreTestCell, _, _> =
+        TwodimGrid::new(3, 3, nhood, Boundary::Klein, EmptyState, 1);
+
+    // Upper row of neighbors comes from mirrored bottom row.
+    let expected = vec![Some(6), Some(8), Some(7), Some(2), Some(1), Some(5), Some(3), Some(4)];
+    assert_eq!(grid.buffer.neighbors[0], expected);
+}
+
+
+#[test]
+fn test_reflect_neighbors() {
+    let nhood = MooreNhood::new();
+    let grid: TwodimGrid<MooreTestCell, _, _> =
+        TwodimGrid::new(3, 3, nhood, Boundary::Reflect, EmptyState, 1);
+
+    // Top-left corner sees itself and its neighbors mirrored.
+    let expected = vec![Some(0), Some(0), Some(1), Some(0), Some(1), Some(3), Some(3), Some(4)];
+    assert_eq!(grid.buffer.neighbors[0], expected);
+}
+
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct GhostTestCell {
+    coord: (i32, i32),
+    ghost: bool,
+}
+
+impl Cell for GhostTestCell {
+    type Coord = (i32, i32);
+    type State = EmptyState;
+
+    fn update<'a, I>(&'a mut self, _: &'a Self, neighbors: I, _: &Self::State)
+        where I: Neighbors<'a, Self>,
+    {
+        // Every neighbor outside of 2x2 grid should be
+        // replaced with ghost cell, so there is no None at all.
+        let ghosts_cnt = neighbors.map(|n| n.expect("ghost cell should be passed"))
+            .filter(|n| n.ghost)
+            .count();
+
+        assert_eq!(ghosts_cnt, 5);
+    }
+
+    fn with_coord<C: Coord>(coord: C) -> Self {
+        GhostTestCell {
+            coord: (coord.x(), coord.y()),
+            ghost: false,
+        }
+    }
+
+    fn coord(&self) -> &Self::Coord { &self.coord }
+
+    fn set_coord<C: Coord>(&mut self, coord: &C) { self.coord = (coord.x(), coord.y()); }
+}
+
+
+#[test]
+fn test_fixed_boundary() {
+    let nhood = MooreNhood::new();
+    let ghost = GhostTestCell {
+        coord: (-1, -1),
+        ghost: true,
+    };
+    let mut grid: TwodimGrid<GhostTestCell, _, _> =
+        TwodimGrid::new(2, 2, nhood, Boundary::Fixed(ghost), EmptyState, 1);
+    grid.update();
+}
+
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct OffsetTestCell {
+    coord: (i32, i32),
+    wrapped: u32,
+}
+
+impl Cell for OffsetTestCell {
+    type Coord = (i32, i32);
+    type State = EmptyState;
+
+    fn update<'a, I>(&'a mut self, _: &'a Self, neighbors: I, _: &Self::State)
+        where I: Neighbors<'a, Self>,
+    {
+        // Neighbor's coord shifted back by its offset gives
+        // the cell's own coord unless neighbor was wrapped.
+        self.wrapped = 0;
+
+        for (neighbor, offset) in neighbors.with_offsets() {
+            let neighbor = neighbor.unwrap();
+
+            let origin = (neighbor.coord.0 - offset.0, neighbor.coord.1 - offset.1);
+            if origin != self.coord {
+                self.wrapped += 1;
+            }
+        }
+    }
+
+    fn with_coord<C: Coord>(coord: C) -> Self {
+        OffsetTestCell {
+            coord: (coord.x(), coord.y()),
+            wrapped: 0,
+        }
+    }
+
+    fn coord(&self) -> &Self::Coord { &self.coord }
+
+    fn set_coord<C: Coord>(&mut self, coord: &C) { self.coord = (coord.x(), coord.y()); }
+}
+
+
+#[test]
+fn test_neighbor_offsets() {
+    let nhood = MooreNhood::new();
+    let mut grid: TwodimGrid<OffsetTestCell, _, _> =
+        TwodimGrid::new(3, 3, nhood, Boundary::Torus, EmptyState, 1);
+
+    // Offsets are the same for every cell, so they are stored once.
+    assert!(grid.buffer.patterns.iter().all(|&pattern| pattern == 0));
+
+    grid.update();
+
+    // Corners have 5 wrapped neighbors, the rest of
+    // border cells have 3 and center has none.
+    let wrapped: Vec<_> = grid.cells().iter().map(|c| c.wrapped).collect();
+    assert_eq!(wrapped, vec![5, 3, 5, 3, 0, 3, 5, 3, 5]);
+}
+
+
+fn diffusion_kernel() -> KernelNhood<GridCoord> {
+    KernelNhood::from_matrix(vec![vec![0.0, 1.0, 0.0], vec![1.0, 0.0, 1.0], vec![0.0, 1.0, 0.0]])
+        .unwrap()
+        .normalized()
+}
+
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct DiffusionTestCell {
+    coord: (i32, i32),
+    value: f64,
+}
+
+impl Cell for DiffusionTestCell {
+    type Coord = (i32, i32);
+    type State = EmptyState;
+
+    fn update<'a, I>(&'a mut self, _: &'a Self, neighbors: I, _: &Self::State)
+        where I: Neighbors<'a, Self>,
+    {
+        assert_eq!(neighbors.weights(), &[0.25, 0.25, 0.25, 0.25]);
+        self.value = neighbors.weighted_sum(|neighbor| neighbor.value);
+    }
+
+    fn with_coord<C: Coord>(coord: C) -> Self {
+        DiffusionTestCell {
+            coord: (coord.x(), coord.y()),
+            value: 0.0,
+        }
+    }
+
+    fn coord(&self) -> &Self::Coord { &self.coord }
+
+    fn set_coord<C: Coord>(&mut self, coord: &C) { self.coord = (coord.x(), coord.y()); }
+}
+
+
+#[test]
+fn test_weighted_sum() {
+    let mut grid: TwodimGrid<DiffusionTestCell, _, _> =
+        TwodimGrid::new(3, 3, diffusion_kernel(), Boundary::Torus, EmptyState, 2);
+
+    grid.set_cells(vec![DiffusionTestCell {
+                            coord: (1, 1),
+                            value: 1.0,
+                        }]);
+
+    assert_eq!(grid.weighted_sum(&(1, 0), |cell| cell.value), 0.25);
+    assert_eq!(grid.weighted_sum(&(0, 0), |cell| cell.value), 0.0);
+
+    grid.update();
+
+    // Value spreads evenly to Von Neumann's neighbors.
+    let values: Vec<_> = grid.cells().iter().map(|c| c.value).collect();
+    assert_eq!(values, vec![0.0, 0.25, 0.0, 0.25, 0.0, 0.25, 0.0, 0.25, 0.0]);
+    assert_eq!(grid.weighted_sum(&(1, 1), |cell| cell.value), 0.25);
+}
+
+
+#[test]
+fn test_weighted_sum_open() {
+    let mut grid: TwodimGrid<DiffusionTestCell, _, _> =
+        TwodimGrid::new(2, 2, diffusion_kernel(), Boundary::Open, EmptyState, 1);
+
+    grid.set_cells(vec![DiffusionTestCell {
+                            coord: (0, 0),
+                            value: 1.0,
+                        }]);
+
+    // Neighbors outside of grid contribute nothing.
+    grid.update();
+    let values: Vec<_> = grid.cells().iter().map(|c| c.value).collect();
+    assert_eq!(values, vec![0.0, 0.25, 0.25, 0.0]);
+}
+
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct SpreadTestCell {
+    coord: (i32, i32),
+    infected: bool,
+}
+
+impl Cell for SpreadTestCell {
+    type Coord = (i32, i32);
+    type State = EmptyState;
+
+    fn update<'a, I>(&'a mut self, old: &'a Self, neighbors: I, _: &Self::State)
+        where I: Neighbors<'a, Self>,
+    {
+        self.infected = old.infected || neighbors.filter_map(|n| n).any(|n| n.infected);
+    }
+
+    fn with_coord<C: Coord>(coord: C) -> Self {
+        SpreadTestCell {
+            coord: (coord.x(), coord.y()),
+            infected: false,
+        }
+    }
+
+    fn coord(&self) -> &Self::Coord { &self.coord }
+
+    fn set_coord<C: Coord>(&mut self, coord: &C) { self.coord = (coord.x(), coord.y()); }
+}
+
+fn spread_grid(mode: UpdateMode) -> TwodimGrid<SpreadTestCell, MooreNhood<GridCoord>, EmptyState> {
+    let mut grid = TwodimGrid::new(1, 8, MooreNhood::new(), Boundary::Open, EmptyState, 2);
+    grid.set_update_mode(mode);
+    grid.count_changes();
+    grid.set_cells(vec![SpreadTestCell {
+                            coord: (0, 0),
+                            infected: true,
+                        }]);
+
+    grid
+}
+
+fn infected(grid: &TwodimGrid<SpreadTestCell, MooreNhood<GridCoord>, EmptyState>) -> Vec<bool> {
+    grid.cells().iter().map(|c| c.infected).collect()
+}
+
+
+#[test]
+fn test_update_modes() {
+    // Synchronous update spreads infection by one cell.
+    let mut grid = spread_grid(UpdateMode::Synchronous);
+    grid.update();
+    assert_eq!(grid.cells().iter().filter(|c| c.infected).count(), 2);
+    assert_eq!(grid.changed(), Some(1));
+
+    // Sweep goes from left to right, so every cell sees
+    // its left neighbor already infected.
+    let mut grid = spread_grid(UpdateMode::Sweep);
+    grid.update();
+    assert!(grid.cells().iter().all(|c| c.infected));
+    assert_eq!(grid.changed(), Some(7));
+
+    // Random order is the same for the same seed
+    // and differs from order of `Sweep`.
+    let identity: Vec<_> = (0..8).collect();
+    let mut first = spread_grid(UpdateMode::RandomOrder(42));
+    let mut second = spread_grid(UpdateMode::RandomOrder(42));
+    let mut orders = Vec::new();
+    for _ in 0..3 {
+        first.update();
+        second.update();
+        assert_eq!(infected(&first), infected(&second));
+        assert_eq!(first.order, second.order);
+        assert!(first.order != identity);
+        orders.push(first.order.clone());
+    }
+    assert!(orders[0] != orders[1] || orders[1] != orders[2]);
+
+    // Reseeding starts the same sequence again.
+    let expected = infected(&first);
+    first.set_cells((0..8).map(|x| SpreadTestCell::with_coord((x, 0))).collect());
+    first.set_cells(vec![SpreadTestCell {
+                             coord: (0, 0),
+                             infected: true,
+                         }]);
+    first.set_update_mode(UpdateMode::RandomOrder(42));
+    for _ in 0..3 {
+        first.update();
+    }
+    assert_eq!(infected(&first), expected);
+}
+
+
+#[test]
+fn test_random_order_checkpoint() {
+    let mut grid = spread_grid(UpdateMode::RandomOrder(7));
+    grid.update();
+
+    let checkpoint = grid.checkpoint(1);
+    let mut restored = TwodimGrid::from_checkpoint(checkpoint, MooreNhood::new(), 1).unwrap();
+
+    // Order of next generations does not depend on
+    // orders grid has used before checkpoint.
+    for _ in 0..3 {
+        grid.update();
+        restored.update();
+        assert_eq!(restored.order, grid.order);
+        assert_eq!(infected(&restored), infected(&grid));
+    }
+}
+
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct CoinTestCell {
+    coord: (i32, i32),
+    value: u64,
+}
+
+impl Cell for CoinTestCell {
+    type Coord = (i32, i32);
+    type State = EmptyState;
+
+    fn update<'a, I>(&'a mut self, _: &'a Self, neighbors: I, _: &Self::State)
+        where I: Neighbors<'a, Self>,
+    {
+        self.value = neighbors.rng().next_u64();
+    }
+
+    fn with_coord<C: Coord>(coord: C) -> Self {
+        CoinTestCell {
+            coord: (coord.x(), coord.y()),
+            value: 0,
+        }
+    }
+
+    fn coord(&self) -> &Self::Coord { &self.coord }
+
+    fn set_coord<C: Coord>(&mut self, coord: &C) { self.coord = (coord.x(), coord.y()); }
+}
+
+fn tossed(seed: u64, probability: f64, threads: u32) -> Vec<u64> {
+    let mut grid: TwodimGrid<CoinTestCell, _, _> =
+        TwodimGrid::new(6, 6, MooreNhood::new(), Boundary::Torus, EmptyState, threads);
+    grid.set_seed(seed);
+    grid.set_update_probability(probability);
+
+    for _ in 0..3 {
+        grid.update();
+    }
+
+    grid.cells().iter().map(|c| c.value).collect()
+}
+
+
+#[test]
+fn test_cell_rng() {
+    // Streams do not depend on threads count.
+    let values = tossed(7, 1.0, 1);
+    assert_eq!(values, tossed(7, 1.0, 4));
+    assert!(values != tossed(8, 1.0, 1));
+
+    // Every cell has its own stream.
+    let mut distinct = values.clone();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(distinct.len(), values.len());
+
+    // Skipped cells are the same for any threads count too.
+    let values = tossed(7, 0.5, 1);
+    assert_eq!(values, tossed(7, 0.5, 3));
+    assert!(values.iter().any(|&value| value == 0));
+    assert!(values.iter().any(|&value| value != 0));
+
+    assert!(tossed(7, 0.0, 2).iter().all(|&value| value == 0));
+}
@@ -16,9 +16,8 @@
 
 extern crate serde;
 extern crate scoped_threadpool;
-
-#[cfg(test)]
 extern crate bincode;
+
 #[cfg(test)]
 extern crate test;
 #[cfg(test)]
@@ -26,7 +25,9 @@ extern crate num_cpus;
 
 pub mod grid;
 pub mod engine;
+pub mod consumer;
 pub mod traits;
+pub mod rng;
 mod utils;
 
 mod examples;
@@ -0,0 +1,129 @@
+//! Small seedable random number generator.
+//! Automata need randomness which can be reproduced
+//! from seed, so global generators do not fit.
+
+/// SplitMix64 generator. It is fast, has
+/// 64 bits of state and passes BigCrush.
+/// Same seed always gives the same sequence.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rng {
+    state: u64,
+}
+
+
+const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+
+impl Rng {
+    /// Constructs generator with given seed.
+    pub fn new(seed: u64) -> Self { Rng { state: seed } }
+
+    /// Constructs generator seeded with `seed` and `keys`.
+    /// Generators with different keys give independent
+    /// sequences, so every cell can have its own stream
+    /// derived from its index and generation.
+    pub fn from_keys(seed: u64, keys: &[u64]) -> Self {
+        let state = keys.iter().fold(mix(seed), |state, &key| mix(state ^ mix(key ^ GAMMA)));
+        Rng::new(state)
+    }
+
+    /// Returns next random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GAMMA);
+        mix(self.state)
+    }
+
+    /// Returns random number from `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 { (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 }
+
+    /// Returns random number from `[0, n)`. `n` should be positive.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "Range should not be empty.");
+
+        // Rejects numbers from incomplete last range
+        // to keep distribution uniform.
+        let zone = u64::max_value() - u64::max_value() % n;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % n;
+            }
+        }
+    }
+
+    /// Shuffles slice in place (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        let mut i = items.len();
+
+        while i > 1 {
+            let j = self.below(i as u64) as usize;
+            i -= 1;
+            items.swap(i, j);
+        }
+    }
+}
+
+
+/// Finalizer of SplitMix64. Also used to
+/// derive independent seeds from several numbers.
+pub fn mix(value: u64) -> u64 {
+    let mut z = value;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::Rng;
+
+    #[test]
+    fn test_rng_is_reproducible() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let mut c = Rng::new(43);
+
+        let first: Vec<_> = (0..8).map(|_| a.next_u64()).collect();
+        let second: Vec<_> = (0..8).map(|_| b.next_u64()).collect();
+        let third: Vec<_> = (0..8).map(|_| c.next_u64()).collect();
+
+        assert_eq!(first, second);
+        assert!(first != third);
+    }
+
+    #[test]
+    fn test_rng_from_keys() {
+        let mut a = Rng::from_keys(1, &[2, 3]);
+        let mut b = Rng::from_keys(1, &[2, 3]);
+        let mut c = Rng::from_keys(1, &[3, 2]);
+
+        let value = a.next_u64();
+        assert_eq!(value, b.next_u64());
+        assert!(value != c.next_u64());
+    }
+
+    #[test]
+    fn test_rng_ranges() {
+        let mut rng = Rng::new(7);
+
+        for _ in 0..1000 {
+            let value = rng.next_f64();
+            assert!(value >= 0.0 && value < 1.0);
+            assert!(rng.below(6) < 6);
+        }
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let mut rng = Rng::new(1);
+        let mut items: Vec<_> = (0..100).collect();
+        rng.shuffle(&mut items);
+
+        assert!(items != (0..100).collect::<Vec<_>>());
+
+        items.sort();
+        assert_eq!(items, (0..100).collect::<Vec<_>>());
+    }
+}
@@ -1,7 +1,16 @@
 //! Interfaces on which this library is built.
 
+use std::iter::Zip;
+use std::slice;
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+use rng::Rng;
+
+/// Relative position `(dx, dy, dz)` of neighbor.
+pub type Offset = (i32, i32, i32);
+
 /// Trait represents global state of the
 /// entire simulation which can be updated
 /// independently from any particular cell.
@@ -28,7 +37,7 @@ pub trait Cell: Serialize + Deserialize {
     /// neighbors, previous version of this Cell and the
     /// global state.
     fn update<'a, I>(&'a mut self, old: &'a Self, neighbors: I, &Self::State)
-        where I: Iterator<Item = Option<&'a Self>>;
+        where I: Neighbors<'a, Self>;
 
     /// Constructs Cell with given coord.
     fn with_coord<C: Coord>(C) -> Self;
@@ -48,14 +57,76 @@ pub trait Nhood {
     fn neighbors(&self, &Self::Coord) -> Vec<Self::Coord>;
     /// Hint for grid.
     fn neighbors_count(&self) -> usize;
+
+    /// Offsets of neighbors relative to given coord
+    /// in the same order as `neighbors` returns them.
+    fn offsets(&self, coord: &Self::Coord) -> Vec<Offset> {
+        self.neighbors(coord)
+            .iter()
+            .map(|n| (n.x() - coord.x(), n.y() - coord.y(), n.z() - coord.z()))
+            .collect()
+    }
+
+    /// Weights of neighbors of given coord in the same
+    /// order as `neighbors` returns them. Weight should depend
+    /// only on offset of neighbor. Empty if neighborhood
+    /// is not weighted.
+    fn weights(&self, _coord: &Self::Coord) -> Vec<f64> { Vec::new() }
+}
+
+/// Neighbors of cell which grid passes to `Cell::update`.
+/// Every neighbor has offset relative to cell, so rules
+/// can tell directions apart without relying on ordering
+/// of neighborhood.
+pub trait Neighbors<'a, C: 'a>: Iterator<Item = Option<&'a C>> {
+    /// Offsets of neighbors in the same order as
+    /// they are yielded.
+    fn offsets(&self) -> &'a [Offset];
+
+    /// Random stream of cell for current generation. Grids
+    /// derive it from their seed, generation and position of
+    /// cell, so it does not depend on threads count. Every
+    /// call returns the same stream. Default impl returns
+    /// the same stream for every cell and generation.
+    fn rng(&self) -> Rng { Rng::new(0) }
+
+    /// Yields neighbors together with their offsets.
+    fn with_offsets(self) -> Zip<Self, slice::Iter<'a, Offset>>
+        where Self: Sized,
+    {
+        let offsets = self.offsets();
+        self.zip(offsets.iter())
+    }
+
+    /// Weights of neighbors in the same order as they are
+    /// yielded. Empty if neighborhood is not weighted.
+    fn weights(&self) -> &'a [f64] { &[] }
+
+    /// Sums projections of neighbors multiplied by their
+    /// weights. Neighbors of unweighted neighborhood weigh 1.
+    /// Missing neighbors contribute nothing.
+    fn weighted_sum<F>(self, projection: F) -> f64
+        where Self: Sized,
+              F: Fn(&C) -> f64,
+    {
+        let weights = self.weights();
+
+        self.enumerate().fold(0.0, |sum, (i, neighbor)| match neighbor {
+            Some(neighbor) => sum + projection(neighbor) * weights.get(i).map_or(1.0, |&w| w),
+            None => sum,
+        })
+    }
 }
 
 /// Basic coordinate with three components.
-/// This crate is used for creating automata
-/// on 2D grids for now, so `z` has default impl.
+/// Most of automata live on 2D grids,
+/// so `z` has default impl.
 pub trait Coord: Clone + Serialize + Deserialize {
     /// Build coord from any other representation of coord.
     fn from_2d(x: i32, y: i32) -> Self;
+    /// Build coord from three components. 2D coords
+    /// just drop `z`.
+    fn from_3d(x: i32, y: i32, _z: i32) -> Self { Self::from_2d(x, y) }
 
     /// Returns `x` component.
     fn x(&self) -> i32;
@@ -78,6 +149,8 @@ pub trait Grid {
 
     /// Getter for evolution state.
     fn state(&self) -> &<<Self as Grid>::Cell as Cell>::State;
+    /// Setter for evolution state.
+    fn set_state(&mut self, <<Self as Grid>::Cell as Cell>::State);
     /// Getter for all cells. It should some type that could
     /// be represented as slice (i.e. `Vec`).
     fn cells(&self) -> &[Self::Cell];
@@ -91,6 +164,29 @@ pub trait Grid {
     /// or from engine (i.e. distributed engine received
     /// updates from nodes).
     fn set_cells(&mut self, Vec<Self::Cell>);
+
+    /// Brings grid back to the state it had when `cells`
+    /// returned given cells. Unlike `set_cells`, it also
+    /// drops whatever is not in the list. Default impl
+    /// calls `set_cells`, which is enough for grids that
+    /// return every cell they have.
+    fn replace_cells(&mut self, cells: Vec<Self::Cell>) { self.set_cells(cells); }
+
+    /// Count of cells changed by last `update`.
+    /// `None` if grid does not track changes.
+    fn changed(&self) -> Option<usize> { None }
+}
+
+/// Describes the step consumer is called after.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Context {
+    /// Count of generations passed.
+    pub generation: u64,
+    /// Wall-clock duration of the step.
+    pub duration: Duration,
+    /// Count of cells changed by the step.
+    /// `None` if grid does not track changes.
+    pub changed: Option<usize>,
 }
 
 /// Interface to the outer world.
@@ -101,12 +197,51 @@ pub trait Consumer {
     type Cell: Cell;
 
     /// Called once when all cells has been updated.
-    fn consume<G>(&mut self, &mut G)
+    /// Engine describes the step with context.
+    fn consume<G>(&mut self, &mut G, &Context)
         where G: Grid<Cell = Self::Cell>;
 }
 
+/// Why evolution has been stopped.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StopReason {
+    /// Predicate over grid became true.
+    Predicate,
+    /// Grid has not changed in last generation.
+    FixedPoint,
+    /// Grid returned to the state it had given
+    /// number of generations ago.
+    Cycle(u64),
+    /// Generations cap has been reached.
+    MaxGenerations,
+}
+
+/// Report returned by `Engine::run_until`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stop {
+    /// Why evolution has been stopped.
+    pub reason: StopReason,
+    /// Generation grid has been stopped at.
+    pub generation: u64,
+}
+
+/// Condition which is checked by engine before
+/// the first step and after every generation.
+/// Built-in conditions live in `engine::stop`.
+pub trait StopCondition<G: Grid> {
+    /// Returns reason to stop or `None` to go on.
+    fn check(&mut self, grid: &G, generation: u64) -> Option<StopReason>;
+}
+
 /// Interlayer between grid and consumer(s).
 pub trait Engine {
+    /// Grid evolved by engine.
+    type Grid: Grid;
+
     /// Runs evolution fixed number of times.
     fn run_times(&mut self, u64);
+
+    /// Runs evolution until condition is met. Returns
+    /// why evolution has been stopped and at which generation.
+    fn run_until<S>(&mut self, S) -> Stop where S: StopCondition<Self::Grid>;
 }
@@ -10,6 +10,15 @@ impl Coord for (i32, i32) {
     fn y(&self) -> i32 { self.1 }
 }
 
+impl Coord for (i32, i32, i32) {
+    fn from_2d(x: i32, y: i32) -> Self { (x, y, 0) }
+    fn from_3d(x: i32, y: i32, z: i32) -> Self { (x, y, z) }
+
+    fn x(&self) -> i32 { self.0 }
+    fn y(&self) -> i32 { self.1 }
+    fn z(&self) -> i32 { self.2 }
+}
+
 
 #[cfg(test)]
 mod test_utils {